@@ -0,0 +1,22 @@
+#![no_std]
+
+use hash_rings::util::SipState;
+use hash_rings::{consistent, jump, rendezvous};
+
+pub fn get_nodes(point: u64) -> (u64, u64, u32) {
+    let nodes = [0u64, 1, 2];
+    let hash_builder = SipState::new(0, 1);
+    let mut consistent = consistent::Ring::with_hasher(hash_builder);
+    let mut rendezvous = rendezvous::Ring::with_hasher(hash_builder);
+    for node in &nodes {
+        consistent.insert_node(node, 10);
+        rendezvous.insert_node(node, 1);
+    }
+    let jump = jump::Ring::with_hasher(hash_builder, 3);
+
+    (
+        *consistent.get_node(&point),
+        *rendezvous.get_node(&point),
+        jump.get_node(&point),
+    )
+}
@@ -0,0 +1,502 @@
+use crate::generator::{Generator, KeyDistribution};
+use crate::{carp, consistent, jump, maglev, metrics, mpc, rendezvous, weighted_rendezvous};
+use crate::{DynamicHashRing, HashRing};
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write;
+use std::hash::Hash;
+
+const COMPARISON_REPLICAS: usize = 10;
+const COMPARISON_HASH_COUNT: u64 = 21;
+
+/// The nodes of a ring whose loads were outside of the tolerance of `assert_balanced`.
+#[derive(Debug)]
+pub struct Imbalance<'a, T> {
+    /// The number of points each node was expected to receive.
+    pub expected: f64,
+    /// The nodes whose loads were outside of the tolerance and the number of points they received.
+    pub nodes: Vec<(&'a T, u64)>,
+    /// The number of nodes that received no points.
+    pub empty_nodes: usize,
+}
+
+impl<'a, T> fmt::Display for Imbalance<'a, T>
+    where
+        T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Expected {:.1} points per node", self.expected)?;
+        for (id, load) in &self.nodes {
+            write!(f, ", {:?} received {}", id, load)?;
+        }
+        if self.empty_nodes > 0 {
+            write!(f, ", {} nodes received no points", self.empty_nodes)?;
+        }
+        Ok(())
+    }
+}
+
+/// The balance of the loads of a hashing algorithm, as reported by `compare_algorithms`.
+#[derive(Clone, Debug)]
+pub struct AlgorithmStats {
+    /// The name of the algorithm.
+    pub name: &'static str,
+    /// The largest load of a node divided by the average load.
+    pub peak_to_average: f64,
+    /// The standard deviation of the loads of the nodes divided by the average load.
+    pub std_dev: f64,
+}
+
+/// Maps `keys` to `nodes` with every hashing algorithm in the crate and returns the balance of the
+/// loads of each algorithm.
+///
+/// Every node has a weight of one. Consistent hashing uses 10 replicas per node and multi-probe
+/// consistent hashing uses 21 hashes per key.
+pub fn compare_algorithms(nodes: &[u64], keys: &[u64]) -> Vec<AlgorithmStats> {
+    assert!(!nodes.is_empty());
+    let mut consistent_ring = consistent::Ring::new();
+    let mut mpc_ring = mpc::Ring::new(COMPARISON_HASH_COUNT);
+    let mut rendezvous_ring = rendezvous::Ring::new();
+    let mut weighted_rendezvous_ring = weighted_rendezvous::Ring::new();
+    for node in nodes {
+        consistent_ring.insert_node(node, COMPARISON_REPLICAS);
+        mpc_ring.insert_node(node);
+        rendezvous_ring.insert_node(node, 1);
+        weighted_rendezvous_ring.insert_node(node, 1.0);
+    }
+    let jump_ring = jump::LabeledRing::new(nodes);
+    let maglev_ring = maglev::Ring::new(nodes.iter().collect());
+    let carp_ring = carp::Ring::new(nodes.iter().map(|node| carp::Node::new(node, 1.0)).collect());
+
+    vec![
+        get_stats("consistent", nodes, keys, |key| consistent_ring.get_node(key)),
+        get_stats("jump", nodes, keys, |key| jump_ring.get_node(key)),
+        get_stats("maglev", nodes, keys, |key| maglev_ring.get_node(key)),
+        get_stats("mpc", nodes, keys, |key| mpc_ring.get_node(key)),
+        get_stats("rendezvous", nodes, keys, |key| rendezvous_ring.get_node(key)),
+        get_stats("carp", nodes, keys, |key| carp_ring.get_node(key)),
+        get_stats("weighted_rendezvous", nodes, keys, |key| {
+            weighted_rendezvous_ring.get_node(key)
+        }),
+    ]
+}
+
+fn get_stats<'a, F>(
+    name: &'static str,
+    nodes: &'a [u64],
+    keys: &[u64],
+    get_node: F,
+) -> AlgorithmStats
+    where
+        F: Fn(&u64) -> &'a u64,
+{
+    let mut loads: HashMap<&u64, u64> = nodes.iter().map(|node| (node, 0)).collect();
+    for key in keys {
+        *loads.entry(get_node(key)).or_insert(0) += 1;
+    }
+    AlgorithmStats {
+        name,
+        peak_to_average: metrics::load_imbalance(&loads),
+        std_dev: metrics::coefficient_of_variation(&loads),
+    }
+}
+
+/// Formats the results of `compare_algorithms` as a table with one row per algorithm.
+pub fn format_comparison_table(stats: &[AlgorithmStats]) -> String {
+    let mut table = format!("{:<20} {:>15} {:>10}\n", "algorithm", "peak/average", "std dev");
+    for stat in stats {
+        writeln!(
+            table,
+            "{:<20} {:>15.4} {:>10.4}",
+            stat.name, stat.peak_to_average, stat.std_dev
+        )
+        .expect("Expected writing to a string to succeed.");
+    }
+    table
+}
+
+/// Formats the results of `compare_algorithms` as a JSON array with one object per algorithm.
+pub fn format_comparison_json(stats: &[AlgorithmStats]) -> String {
+    let objects: Vec<String> = stats
+        .iter()
+        .map(|stat| {
+            format!(
+                "{{\"algorithm\":\"{}\",\"peak_to_average\":{},\"std_dev\":{}}}",
+                stat.name, stat.peak_to_average, stat.std_dev
+            )
+        })
+        .collect();
+    format!("[{}]", objects.join(","))
+}
+
+/// Returns the fraction of `keys` that map to a different node after `removed_id` is removed from
+/// `ring`.
+///
+/// The node is removed from `ring`. For rings with minimal disruption, the only keys that move are
+/// the keys that were previously mapped to `removed_id`.
+pub fn reassignment_on_removal<'a, R, T, K>(ring: &mut R, keys: &[K], removed_id: &T) -> f64
+    where
+        R: DynamicHashRing<'a, T, K>,
+        T: 'a + Eq,
+{
+    let before: Vec<&'a T> = keys.iter().map(|key| ring.get_node(key)).collect();
+    ring.remove_node(removed_id);
+    let moved = keys
+        .iter()
+        .zip(before)
+        .filter(|(key, node)| ring.get_node(key) != *node)
+        .count();
+    moved as f64 / keys.len() as f64
+}
+
+/// Returns the number of `keys` that are mapped to each node of `ring`. Nodes that no key is mapped
+/// to are omitted.
+pub fn simulate_distribution<'a, R, T, K>(ring: &R, keys: &[K]) -> Vec<(&'a T, u64)>
+    where
+        R: HashRing<'a, T, K>,
+        T: 'a + Hash + Eq,
+{
+    let mut counts: HashMap<&'a T, u64> = HashMap::new();
+    for key in keys {
+        *counts.entry(ring.get_node(key)).or_insert(0) += 1;
+    }
+    counts.into_iter().collect()
+}
+
+/// Routes `samples` points drawn from `dist` through `ring` and checks that every node received a
+/// fair share of the points. The points are drawn by a generator seeded with `seed`, so the same
+/// ring and arguments always give the same result.
+///
+/// A node's load is fair if it differs from the average load by at most `tolerance`, relative to
+/// the average load. For example, a `tolerance` of `0.1` accepts loads within 10% of the average.
+pub fn assert_balanced<'a, R, T>(
+    ring: &R,
+    dist: KeyDistribution,
+    seed: u64,
+    samples: u64,
+    tolerance: f64,
+) -> Result<(), Imbalance<'a, T>>
+    where
+        R: HashRing<'a, T, u64>,
+        T: 'a + Hash + Eq,
+{
+    let points = Generator::with_seed(dist, seed).next_n(samples);
+    let loads = simulate_distribution(ring, &points);
+
+    let expected = samples as f64 / ring.len() as f64;
+    // Nodes that received no points are missing from `loads`, and their load is only outside of
+    // the tolerance if the tolerance is less than one.
+    let empty_nodes = if tolerance < 1.0 {
+        ring.len() - loads.len()
+    } else {
+        0
+    };
+    let nodes: Vec<(&'a T, u64)> = loads
+        .into_iter()
+        .filter(|(_, load)| (*load as f64 - expected).abs() > tolerance * expected)
+        .collect();
+
+    if nodes.is_empty() && empty_nodes == 0 {
+        Ok(())
+    } else {
+        Err(Imbalance {
+            expected,
+            nodes,
+            empty_nodes,
+        })
+    }
+}
+
+/// Returns the relative error of the load of each node against a uniform distribution of `total`
+/// points, `|expected - observed| / expected`.
+///
+/// Every node must be in `observed`, including nodes that received no points, since the expected
+/// load is `total` divided by the number of nodes.
+pub fn load_errors<K>(observed: &HashMap<K, u64>, total: u64) -> HashMap<K, f64>
+    where
+        K: Hash + Eq + Clone,
+{
+    let share = 1.0 / observed.len() as f64;
+    let shares = observed.keys().map(|id| (id.clone(), share)).collect();
+    load_errors_with_shares(observed, &shares, total)
+}
+
+/// Returns the relative error of the load of each node against its expected share of `total`
+/// points, `|expected - observed| / expected`. Nodes that are missing from `observed` are assumed
+/// to have received no points.
+///
+/// This is the equivalent of `load_errors` for weighted rings, where the expected share of a node
+/// is its weight divided by the total weight.
+pub fn load_errors_with_shares<K>(
+    observed: &HashMap<K, u64>,
+    expected_shares: &HashMap<K, f64>,
+    total: u64,
+) -> HashMap<K, f64>
+    where
+        K: Hash + Eq + Clone,
+{
+    expected_shares
+        .iter()
+        .map(|(id, share)| {
+            let expected = share * total as f64;
+            let actual = *observed.get(id).unwrap_or(&0) as f64;
+            (id.clone(), (expected - actual).abs() / expected)
+        })
+        .collect()
+}
+
+/// Returns the nodes of `ring` that none of `keys` are mapped to.
+///
+/// Nodes that receive no keys from a sample of the workload are a sign that the ring has too few
+/// replicas for the number of keys. See `min_replicas_for_coverage`.
+pub fn unused_nodes<'a, R, T, K>(ring: &R, keys: &[K]) -> Vec<&'a T>
+    where
+        R: HashRing<'a, T, K>,
+        T: 'a + Hash + Eq,
+{
+    let used: HashMap<&'a T, u64> = simulate_distribution(ring, keys).into_iter().collect();
+    ring.node_ids()
+        .into_iter()
+        .filter(|id| !used.contains_key(id))
+        .collect()
+}
+
+/// Returns the number of replicas per node that makes it unlikely that a node of a consistent
+/// hashing ring receives none of `num_keys` keys, or `None` if no number of replicas achieves this.
+///
+/// The fraction of the ring owned by a node with `r` replicas approximately follows a gamma
+/// distribution, so the probability that a node receives no keys is `(1 + k / (n * r)) ^ -r`. The
+/// returned count is the smallest `r` for which the union bound over all nodes is at most
+/// `1 - confidence`. Even with infinitely many replicas this probability is at least
+/// `n * e ^ (-k / n)`, so there is no suitable count if there are too few keys per node.
+pub fn min_replicas_for_coverage(
+    num_nodes: usize,
+    num_keys: usize,
+    confidence: f64,
+) -> Option<usize> {
+    assert!(confidence > 0.0 && confidence < 1.0);
+    let n = num_nodes as f64;
+    let k = num_keys as f64;
+    let p_empty = |replicas: usize| {
+        let r = replicas as f64;
+        n * (-r * (k / (n * r)).ln_1p()).exp()
+    };
+    let target = 1.0 - confidence;
+    if num_nodes == 0 || n * (-k / n).exp() >= target {
+        return None;
+    }
+
+    let mut high = 1;
+    while p_empty(high) > target {
+        high *= 2;
+    }
+    let mut low = high / 2 + 1;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if p_empty(mid) > target {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Some(high)
+}
+
+#[cfg(test)]
+fn assert_minimal_disruption<'a, R>(ring: &mut R, removed_id: &'a u64)
+    where
+        R: DynamicHashRing<'a, u64, u64>,
+{
+    let keys: Vec<u64> = (0..10_000).collect();
+    let removed_keys = keys
+        .iter()
+        .filter(|key| ring.get_node(key) == removed_id)
+        .count();
+    let reassigned = reassignment_on_removal(ring, &keys, removed_id);
+
+    assert_eq!(reassigned, removed_keys as f64 / keys.len() as f64);
+}
+
+#[test]
+fn test_reassignment_on_removal_rendezvous() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = crate::rendezvous::Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 3);
+    }
+
+    assert_minimal_disruption(&mut ring, &nodes[4]);
+}
+
+#[test]
+fn test_reassignment_on_removal_weighted_rendezvous() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = crate::weighted_rendezvous::Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, (*node + 1) as f64);
+    }
+
+    assert_minimal_disruption(&mut ring, &nodes[4]);
+}
+
+#[test]
+fn test_reassignment_on_removal_consistent() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = crate::consistent::Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+
+    assert_minimal_disruption(&mut ring, &nodes[4]);
+}
+
+#[test]
+fn test_reassignment_on_removal_mpc() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = crate::mpc::Ring::new(21);
+    for node in &nodes {
+        ring.insert_node(node);
+    }
+
+    assert_minimal_disruption(&mut ring, &nodes[4]);
+}
+
+#[test]
+fn test_assert_balanced() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = crate::rendezvous::Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 1);
+    }
+
+    let dist = KeyDistribution::uniform_distribution();
+    assert!(assert_balanced(&ring, dist, 7, 100_000, 0.2).is_ok());
+}
+
+#[test]
+fn test_assert_balanced_under_replicated() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = crate::consistent::Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 1);
+    }
+
+    let get_imbalance = || {
+        let dist = KeyDistribution::uniform_distribution();
+        let mut imbalance = assert_balanced(&ring, dist, 7, 100_000, 0.1).unwrap_err();
+        imbalance.nodes.sort();
+        imbalance
+    };
+    let imbalance = get_imbalance();
+    assert!(!imbalance.nodes.is_empty() || imbalance.empty_nodes > 0);
+    assert_eq!(imbalance.expected, 10_000.0);
+
+    let other = get_imbalance();
+    assert_eq!(imbalance.nodes, other.nodes);
+    assert_eq!(imbalance.empty_nodes, other.empty_nodes);
+}
+
+#[test]
+fn test_simulate_distribution() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let keys: Vec<u64> = (0..10_000).collect();
+    let ring = crate::jump::LabeledRing::new(&nodes);
+    let distribution = simulate_distribution(&ring, &keys);
+
+    assert_eq!(distribution.len(), nodes.len());
+    assert_eq!(
+        distribution.iter().map(|(_, count)| count).sum::<u64>(),
+        keys.len() as u64,
+    );
+}
+
+#[test]
+fn test_min_replicas_for_coverage() {
+    use crate::consistent;
+
+    assert_eq!(min_replicas_for_coverage(100, 10, 0.99), None);
+    let replicas = min_replicas_for_coverage(20, 200, 0.99).unwrap();
+    assert_eq!(replicas, 15);
+
+    let nodes: Vec<u64> = (0..20).collect();
+    let keys: Vec<u64> = (0..200).collect();
+    let coverage_failures = |replicas| {
+        (0..100)
+            .filter(|_| {
+                let mut ring = consistent::Ring::new();
+                for node in &nodes {
+                    ring.insert_node(node, replicas);
+                }
+                simulate_distribution(&ring, &keys).len() < nodes.len()
+            })
+            .count()
+    };
+    assert!(coverage_failures(replicas) <= 5);
+    assert!(coverage_failures(1) > coverage_failures(replicas));
+}
+
+#[test]
+fn test_unused_nodes() {
+    let nodes: Vec<u64> = (0..100).collect();
+    let keys: Vec<u64> = (0..50).collect();
+    let mut ring = crate::consistent::Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 1);
+    }
+
+    let unused = unused_nodes(&ring, &keys);
+    assert!(unused.len() >= nodes.len() - keys.len());
+    for node in &unused {
+        assert!(keys.iter().all(|key| ring.get_node(key) != *node));
+    }
+    let used = simulate_distribution(&ring, &keys);
+    assert_eq!(unused.len() + used.len(), nodes.len());
+}
+
+#[test]
+fn test_compare_algorithms() {
+    let nodes: Vec<u64> = (0..5).collect();
+    let keys: Vec<u64> = (0..100).collect();
+    let stats = compare_algorithms(&nodes, &keys);
+
+    assert_eq!(stats.len(), 7);
+    for stat in &stats {
+        assert!(stat.peak_to_average >= 1.0);
+        assert!(stat.std_dev >= 0.0);
+    }
+    assert_eq!(format_comparison_table(&stats).lines().count(), 8);
+    assert!(format_comparison_json(&stats).starts_with("[{\"algorithm\":\"consistent\""));
+}
+
+#[test]
+fn test_compare_algorithms_no_keys() {
+    let nodes: Vec<u64> = (0..5).collect();
+    let stats = compare_algorithms(&nodes, &[]);
+
+    assert_eq!(stats.len(), 7);
+    for stat in &stats {
+        assert_eq!(stat.peak_to_average, 1.0);
+        assert_eq!(stat.std_dev, 0.0);
+    }
+}
+
+#[test]
+fn test_load_errors() {
+    let observed: HashMap<&str, u64> = [("a", 30), ("b", 20), ("c", 10), ("d", 40)]
+        .iter()
+        .cloned()
+        .collect();
+
+    let errors = load_errors(&observed, 100);
+    assert!((errors["a"] - 0.2).abs() < 1e-9);
+    assert!((errors["b"] - 0.2).abs() < 1e-9);
+    assert!((errors["c"] - 0.6).abs() < 1e-9);
+    assert!((errors["d"] - 0.6).abs() < 1e-9);
+
+    let shares: HashMap<&str, f64> = [("a", 0.3), ("b", 0.2), ("c", 0.1), ("d", 0.4)]
+        .iter()
+        .cloned()
+        .collect();
+    let errors = load_errors_with_shares(&observed, &shares, 100);
+    assert!(errors.values().all(|error| error.abs() < 1e-9));
+}
@@ -0,0 +1,212 @@
+use crate::util;
+use crate::Router;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::hash::{BuildHasher, Hash};
+
+/// A hashing ring implemented using anchor hashing.
+///
+/// Anchor hashing maps keys to buckets in the range `[0, capacity)`, where the capacity is fixed
+/// when the ring is constructed and bounds the number of working buckets. A key is first hashed to
+/// a bucket in the anchor set. If that bucket was removed, the key is rehashed to one of the
+/// buckets that were working when it was removed, until it lands on a working bucket.
+///
+/// Unlike jump hashing, any bucket can be removed, and only the keys that were mapped to it move.
+/// Buckets are added back in the reverse order of their removal, and adding a bucket back restores
+/// the keys that were mapped to it. Lookups take `O(1)` time on average and the ring uses a
+/// constant number of words per bucket of capacity.
+pub struct Ring<H = util::DefaultState> {
+    // The number of working buckets when a bucket was removed, or zero for a working bucket.
+    anchor: Vec<u32>,
+    // The working buckets in the first `len` entries, followed by the removed buckets.
+    working: Vec<u32>,
+    // The index of each bucket in `working`.
+    locations: Vec<u32>,
+    // The bucket that replaced each removed bucket in `working`.
+    successors: Vec<u32>,
+    removed: Vec<u32>,
+    len: u32,
+    hash_builder: H,
+}
+
+impl Ring<util::DefaultState> {
+    /// Constructs a new `Ring` with a specified capacity. Every bucket is working.
+    pub fn new(capacity: u32) -> Self {
+        Self::with_hasher(Default::default(), capacity)
+    }
+}
+
+impl<H> Ring<H> {
+    /// Constructs a new `Ring` with a specified capacity and hash builder. Every bucket is working.
+    pub fn with_hasher(hash_builder: H, capacity: u32) -> Self {
+        assert!(capacity > 0);
+        Self {
+            anchor: vec![0; capacity as usize],
+            working: (0..capacity).collect(),
+            locations: (0..capacity).collect(),
+            successors: (0..capacity).collect(),
+            removed: Vec::new(),
+            len: capacity,
+            hash_builder,
+        }
+    }
+
+    /// Removes a working bucket from the ring. Only the keys that were mapped to the bucket move.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bucket is not working or if it is the last working bucket.
+    pub fn remove_bucket(&mut self, bucket: u32) {
+        assert!(self.is_working(bucket), "Error: bucket is not working.");
+        assert!(self.len > 1, "Error: cannot remove the last bucket.");
+        let b = bucket as usize;
+        self.removed.push(bucket);
+        self.len -= 1;
+        self.anchor[b] = self.len;
+        let last = self.working[self.len as usize];
+        self.working[self.locations[b] as usize] = last;
+        self.locations[last as usize] = self.locations[b];
+        self.successors[b] = last;
+    }
+
+    /// Adds back the most recently removed bucket and returns it, or returns `None` if every bucket
+    /// is working. The keys that were mapped to the bucket before it was removed are mapped to it
+    /// again.
+    pub fn add_bucket(&mut self) -> Option<u32> {
+        let bucket = self.removed.pop()?;
+        let b = bucket as usize;
+        self.anchor[b] = 0;
+        let last = self.working[self.len as usize];
+        self.locations[last as usize] = self.len;
+        self.working[self.locations[b] as usize] = bucket;
+        self.successors[b] = bucket;
+        self.len += 1;
+        Some(bucket)
+    }
+
+    /// Returns the bucket associated with a key.
+    pub fn get_bucket<U>(&self, key: &U) -> u32
+        where
+            U: Hash,
+            H: BuildHasher,
+    {
+        let key_hash = util::gen_hash(&self.hash_builder, key);
+        let mut bucket = (key_hash % self.capacity() as u64) as u32;
+        while self.anchor[bucket as usize] > 0 {
+            let size = self.anchor[bucket as usize];
+            let hash = util::combine_hash(&self.hash_builder, key_hash, u64::from(bucket));
+            let mut next = (hash % u64::from(size)) as u32;
+            // Buckets that were removed before `bucket` have a greater anchor value, and their
+            // keys moved to their successors.
+            while self.anchor[next as usize] >= size {
+                next = self.successors[next as usize];
+            }
+            bucket = next;
+        }
+        bucket
+    }
+
+    /// Returns `true` if the bucket is working.
+    pub fn is_working(&self, bucket: u32) -> bool {
+        bucket < self.capacity() && self.anchor[bucket as usize] == 0
+    }
+
+    /// Returns the working buckets, in no particular order.
+    pub fn buckets(&self) -> &[u32] {
+        &self.working[..self.len as usize]
+    }
+
+    /// Returns the maximum number of working buckets.
+    pub fn capacity(&self) -> u32 {
+        self.anchor.len() as u32
+    }
+
+    /// Returns the number of working buckets.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Returns `true` if the ring has no working buckets. The last working bucket cannot be
+    /// removed, so this is always `false`.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<H> Router for Ring<H>
+    where
+        H: BuildHasher,
+{
+    type Output = u32;
+
+    fn route<K>(&self, key: &K) -> u32
+        where
+            K: Hash,
+    {
+        self.get_bucket(key)
+    }
+}
+
+#[test]
+fn test_remove_bucket() {
+    let keys: Vec<u64> = (0..10_000).collect();
+    let mut ring = Ring::new(100);
+    let before: Vec<u32> = keys.iter().map(|key| ring.get_bucket(key)).collect();
+
+    ring.remove_bucket(7);
+    ring.remove_bucket(42);
+    assert_eq!(ring.len(), 98);
+    for (key, bucket) in keys.iter().zip(&before) {
+        let after = ring.get_bucket(key);
+        assert!(ring.is_working(after));
+        if *bucket != 7 && *bucket != 42 {
+            assert_eq!(after, *bucket);
+        }
+    }
+
+    assert_eq!(ring.add_bucket(), Some(42));
+    assert_eq!(ring.add_bucket(), Some(7));
+    for (key, bucket) in keys.iter().zip(&before) {
+        assert_eq!(ring.get_bucket(key), *bucket);
+    }
+}
+
+#[test]
+fn test_capacity() {
+    let mut ring = Ring::new(10);
+    assert_eq!(ring.add_bucket(), None);
+    for bucket in 0..9 {
+        ring.remove_bucket(bucket);
+    }
+    assert_eq!(ring.buckets(), &[9]);
+    for key in 0..100 {
+        assert_eq!(ring.get_bucket(&key), 9);
+    }
+
+    while ring.add_bucket().is_some() {}
+    assert_eq!(ring.len(), 10);
+    assert!(!ring.is_working(10));
+    for key in 0..1000 {
+        assert!(ring.get_bucket(&key) < ring.capacity());
+    }
+}
+
+#[test]
+fn test_distribution() {
+    let mut ring = Ring::new(20);
+    for bucket in (0..20).step_by(2) {
+        ring.remove_bucket(bucket);
+    }
+
+    let mut counts = [0u64; 20];
+    for key in 0..100_000u64 {
+        counts[ring.get_bucket(&key) as usize] += 1;
+    }
+    for (bucket, count) in counts.iter().enumerate() {
+        if bucket % 2 == 0 {
+            assert_eq!(*count, 0);
+        } else {
+            assert!((*count as f64 / 10_000.0 - 1.0).abs() < 0.1);
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use hash_rings::{consistent, jump, carp, maglev, mpc, rendezvous, weighted_rendezvous};
 use hash_rings::generator::{Generator, KeyDistribution};
 use hash_rings::util;
+use hash_rings::{analysis, metrics, HashRing};
 
 use std::fs::OpenOptions;
 use std::io::prelude::*;
@@ -15,40 +16,6 @@ const REPLICAS: u64 = 10;
 const ITEMS: u64 = 100_000;
 const NODES: u64 = 10;
 
-fn mean(data: &[f64]) -> Option<f64> {
-    let sum = data.iter().sum::<f64>();
-    let count = data.len();
-
-    match count {
-        positive if positive > 0 => Some(sum / count as f64),
-        _ => None,
-    }
-}
-
-fn std_deviation(data: &[f64]) -> Option<f64> {
-    match (mean(data), data.len()) {
-        (Some(data_mean), count) if count > 0 => {
-            let variance = data.iter().map(|value| {
-                let diff = data_mean - (*value as f64);
-
-                diff * diff
-            }).sum::<f64>() / count as f64;
-
-            Some(variance.sqrt())
-        },
-        _ => None
-    }
-}
-
-fn print_node_statistic(id: u64, expected: f64, actual: f64) -> f64 {
-    let error = (expected - actual).abs() / expected;
-    println!(
-        "{:020} - Expected: {:.6} | Actual: {:.6} | Error: {:9.6}",
-        id, expected, actual, error,
-    );
-    error
-}
-
 fn print_bench_statistic(num_items : u64, duration: Duration) -> f64 {
     let total_time = duration.as_secs() as f64 * 1e9 + f64::from(duration.subsec_nanos());
     let ns_per_op = total_time / num_items as f64;
@@ -61,8 +28,8 @@ fn print_bench_statistic(num_items : u64, duration: Duration) -> f64 {
     ops_per_ns
 }
 
-fn print_std_error(num_nodes: u64, variances: &[f64]) -> (f64, String){
-    let std_error = std_deviation(variances).unwrap();
+fn print_std_error(num_nodes: u64, occupancy: &HashMap<&u64, u64>) -> (f64, String){
+    let std_error = metrics::coefficient_of_variation(occupancy);
     let confidence_interval = 2.576 * std_error / (num_nodes as f64).sqrt();
     let left  = 1.0 - confidence_interval / 2.0;
     let right = 1.0 + confidence_interval / 2.0;
@@ -71,6 +38,15 @@ fn print_std_error(num_nodes: u64, variances: &[f64]) -> (f64, String){
     (std_error, confidence_interval)
 }
 
+fn get_occupancy<'a, R>(ring: &R, nodes: &'a [u64], workload: &[u64]) -> HashMap<&'a u64, u64>
+where
+    R: HashRing<'a, u64, u64>,
+{
+    let mut occ_map: HashMap<&u64, u64> = nodes.iter().map(|node| (node, 0)).collect();
+    occ_map.extend(analysis::simulate_distribution(ring, workload));
+    occ_map
+}
+
 fn write_bench_statistic(num_items: u64, num_nodes: u64, dis: KeyDistribution, throughput: f64, std_error: f64, confidence_interval: String, latency: String, output_filename: String) {
     let output_str = format!("{}\t{}\t{}\t{:}\t{}\t{}\t{}\n", num_items, num_nodes, num_items/num_nodes, dis, throughput, std_error, confidence_interval);
     let file_path = format!("./src/scripts/{}.csv", output_filename);
@@ -103,7 +79,6 @@ fn bench_consistent(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     let mut rng = rand::thread_rng();
     let die = rand_distr::Normal::new(5.0, 1.0).unwrap();
 
-    let mut occ_map = HashMap::new();
     let mut latency_map = HashMap::new();
     let mut latencies = vec![0f64; num_items as usize];
 
@@ -113,7 +88,6 @@ fn bench_consistent(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
 
     for _ in 0..num_nodes {
         let id = rng.gen::<u64>();
-        occ_map.insert(id, 0f64);
         latency_map.insert(id, 0f64);
         nodes.push(id);
     }
@@ -128,7 +102,6 @@ fn bench_consistent(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     let start = Instant::now();
     for (i, item) in workload.iter().enumerate() {
         let id = ring.get_node(&item);
-        *occ_map.get_mut(id).unwrap() += 1.0;
 
         // calculate latency
         let response_time = rng.sample(die);
@@ -139,15 +112,9 @@ fn bench_consistent(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
 
     let throughput = print_bench_statistic(num_items, start.elapsed());
 
-    let variances = nodes.iter()
-        .map(|node| {
-            let actual_load = occ_map[&node] / num_items as f64;
-            let expected_load = 1.0 / num_nodes as f64;
-            actual_load/expected_load
-        })
-        .collect::<Vec<_>>();
+    let occupancy = get_occupancy(&ring, &nodes, &workload);
 
-    let (std_error, confidence_interval) = print_std_error(num_nodes, &variances);
+    let (std_error, confidence_interval) = print_std_error(num_nodes, &occupancy);
 
     let latency = latencies.iter()
         .map(|v| v.to_string())
@@ -165,15 +132,14 @@ fn bench_jump(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     let mut rng = rand::thread_rng();
     let die = rand_distr::Normal::new(5.0, 1.0).unwrap();
 
-    let mut occ_map = HashMap::new();
     let mut latency_map = HashMap::new();
     let mut latencies = vec![0f64; num_items as usize];
 
-    let ring = jump::Ring::new(num_nodes as u32);
+    let nodes: Vec<u64> = (0..num_nodes).collect();
+    let ring = jump::LabeledRing::new(&nodes);
 
-    for i in 0..num_nodes {
-        occ_map.insert(i, 0f64);
-        latency_map.insert(i, 0f64);
+    for node in &nodes {
+        latency_map.insert(*node, 0f64);
     }
 
     let mut key_generator = Generator::new(dis);
@@ -181,27 +147,20 @@ fn bench_jump(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
 
     let start = Instant::now();
     for (i, item) in workload.iter().enumerate() {
-        let id = ring.get_node(&item) as u64;
-        *occ_map.get_mut(&id).unwrap() += 1.0;
+        let id = ring.get_node(&item);
 
         // calculate latency
         let response_time = rng.sample(die);
-        *latency_map.get_mut(&id).unwrap() += response_time;
-        let latency = *latency_map.get_mut(&id).unwrap();
+        *latency_map.get_mut(id).unwrap() += response_time;
+        let latency = *latency_map.get_mut(id).unwrap();
         latencies[i] = latency;
     }
 
     let throughput = print_bench_statistic(num_items, start.elapsed());
 
-    let variances = (0..num_nodes)
-        .map(|i| {
-            let actual_load = occ_map[&i] / num_items as f64;
-            let expected_load = 1.0 / num_nodes as f64;
-            actual_load/expected_load
-        })
-        .collect::<Vec<_>>();
+    let occupancy = get_occupancy(&ring, &nodes, &workload);
 
-    let (std_error, confidence_interval) = print_std_error(num_nodes, &variances);
+    let (std_error, confidence_interval) = print_std_error(num_nodes, &occupancy);
 
     let latency = latencies.iter()
         .map(|v| v.to_string())
@@ -219,7 +178,6 @@ fn bench_maglev(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     let mut rng = rand::thread_rng();
     let die = rand_distr::Normal::new(5.0, 1.0).unwrap();
 
-    let mut occ_map = HashMap::new();
     let mut latency_map = HashMap::new();
     let mut latencies = vec![0f64; num_items as usize];
 
@@ -228,7 +186,6 @@ fn bench_maglev(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     for _ in 0..num_nodes {
         let id = rng.gen::<u64>();
 
-        occ_map.insert(id, 0f64);
         latency_map.insert(id, 0f64);
         nodes.push(id);
     }
@@ -241,7 +198,6 @@ fn bench_maglev(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     let start = Instant::now();
     for (i, item) in workload.iter().enumerate() {
         let id = ring.get_node(&item);
-        *occ_map.get_mut(id).unwrap() += 1.0;
 
         // calculate latency
         let response_time = rng.sample(die);
@@ -252,15 +208,9 @@ fn bench_maglev(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
 
     let throughput = print_bench_statistic(num_items, start.elapsed());
 
-    let variances = nodes.iter()
-        .map(|node| {
-            let actual_load = occ_map[&node] / num_items as f64;
-            let expected_load = 1.0 / num_nodes as f64;
-            actual_load/expected_load
-        })
-        .collect::<Vec<_>>();
+    let occupancy = get_occupancy(&ring, &nodes, &workload);
 
-    let (std_error, confidence_interval) = print_std_error(num_nodes, &variances);
+    let (std_error, confidence_interval) = print_std_error(num_nodes, &occupancy);
 
     let latency = latencies.iter()
         .map(|v| v.to_string())
@@ -278,7 +228,6 @@ fn bench_mpc(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     let mut rng = rand::thread_rng();
     let die = rand_distr::Normal::new(5.0, 1.0).unwrap();
 
-    let mut occ_map = HashMap::new();
     let mut latency_map = HashMap::new();
     let mut latencies = vec![0f64; num_items as usize];
 
@@ -288,7 +237,6 @@ fn bench_mpc(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     for _ in 0..num_nodes {
         let id = rng.gen::<u64>();
 
-        occ_map.insert(id, 0f64);
         latency_map.insert(id, 0f64);
         nodes.push(id);
     }
@@ -303,7 +251,6 @@ fn bench_mpc(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     let start = Instant::now();
     for (i, item) in workload.iter().enumerate() {
         let id = ring.get_node(&item);
-        *occ_map.get_mut(id).unwrap() += 1.0;
 
         // calculate latency
         let response_time = rng.sample(die);
@@ -314,15 +261,9 @@ fn bench_mpc(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
 
     let throughput = print_bench_statistic(num_items, start.elapsed());
 
-    let variances = nodes.iter()
-        .map(|node| {
-            let actual_load = occ_map[&node] / num_items as f64;
-            let expected_load = 1.0 / num_nodes as f64;
-            actual_load/expected_load
-        })
-        .collect::<Vec<_>>();
+    let occupancy = get_occupancy(&ring, &nodes, &workload);
 
-    let (std_error, confidence_interval) = print_std_error(num_nodes, &variances);
+    let (std_error, confidence_interval) = print_std_error(num_nodes, &occupancy);
 
     let latency = latencies.iter()
         .map(|v| v.to_string())
@@ -340,7 +281,6 @@ fn bench_rendezvous(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     let mut rng = rand::thread_rng();
     let die = rand_distr::Normal::new(5.0, 1.0).unwrap();
 
-    let mut occ_map = HashMap::new();
     let mut latency_map = HashMap::new();
     let mut latencies = vec![0f64; num_items as usize];
 
@@ -350,7 +290,6 @@ fn bench_rendezvous(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     for _ in 0..num_nodes {
         let id = rng.gen::<u64>();
 
-        occ_map.insert(id, 0f64);
         latency_map.insert(id, 0f64);
         nodes.push(id);
     }
@@ -365,7 +304,6 @@ fn bench_rendezvous(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     let start = Instant::now();
     for (i, item) in workload.iter().enumerate() {
         let id = ring.get_node(&item);
-        *occ_map.get_mut(id).unwrap() += 1.0;
 
         // calculate latency
         let response_time = rng.sample(die);
@@ -376,15 +314,9 @@ fn bench_rendezvous(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
 
     let throughput = print_bench_statistic(num_items, start.elapsed());
 
-    let variances = nodes.iter()
-        .map(|node| {
-            let actual_load = occ_map[&node] / num_items as f64;
-            let expected_load = 1.0 / num_nodes as f64;
-            actual_load/expected_load
-        })
-        .collect::<Vec<_>>();
+    let occupancy = get_occupancy(&ring, &nodes, &workload);
 
-    let (std_error, confidence_interval) = print_std_error(num_nodes, &variances);
+    let (std_error, confidence_interval) = print_std_error(num_nodes, &occupancy);
 
     let latency = latencies.iter()
         .map(|v| v.to_string())
@@ -394,6 +326,103 @@ fn bench_rendezvous(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
     write_bench_statistic(num_items, num_nodes, dis, throughput, std_error, confidence_interval, latency, String::from("rendezvous_hashing"));
 }
 
+fn bench_frozen(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
+    println!(
+        "\nBenching frozen consistent hashing ({} nodes, {} replicas, {} items, {})",
+        num_nodes, REPLICAS, num_items, dis
+    );
+    let mut rng = rand::thread_rng();
+
+    let mut nodes = Vec::new();
+    let mut ring = consistent::Ring::new();
+
+    for _ in 0..num_nodes {
+        nodes.push(rng.gen::<u64>());
+    }
+
+    for node in &nodes {
+        ring.insert_node(node, REPLICAS as usize);
+    }
+
+    let mut key_generator = Generator::new(dis);
+    let workload: Vec<u64> = key_generator.next_n(num_items);
+
+    println!("Live ring:");
+    let start = Instant::now();
+    for item in &workload {
+        ring.get_node(item);
+    }
+    print_bench_statistic(num_items, start.elapsed());
+
+    let frozen = ring.freeze();
+
+    println!("Frozen ring:");
+    let start = Instant::now();
+    for item in &workload {
+        frozen.get_node(item);
+    }
+    print_bench_statistic(num_items, start.elapsed());
+}
+
+fn bench_integer_keys(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
+    println!(
+        "\nBenching bulk integer lookups ({} nodes, {} replicas, {} items, {})",
+        num_nodes, REPLICAS, num_items, dis
+    );
+    let mut rng = rand::thread_rng();
+
+    let mut nodes = Vec::new();
+    let mut ring = consistent::Ring::with_hasher(util::IntegerState);
+    let jump_ring = jump::Ring::with_hasher(util::IntegerState, num_nodes as u32);
+
+    for _ in 0..num_nodes {
+        nodes.push(rng.gen::<u64>());
+    }
+
+    for node in &nodes {
+        ring.insert_node(node, REPLICAS as usize);
+    }
+
+    let mut key_generator = Generator::new(dis);
+    let workload: Vec<u64> = key_generator.next_n(num_items);
+
+    println!("Consistent hashing, per key:");
+    let start = Instant::now();
+    let per_key: Vec<&u64> = workload.iter().map(|item| ring.get_node(item)).collect();
+    print_bench_statistic(num_items, start.elapsed());
+
+    println!("Consistent hashing, bulk:");
+    let start = Instant::now();
+    let bulk = ring.get_nodes_u64(&workload);
+    print_bench_statistic(num_items, start.elapsed());
+    assert_eq!(per_key, bulk);
+
+    println!("Jump hashing, per key:");
+    let start = Instant::now();
+    let per_key: Vec<u32> = workload.iter().map(|item| jump_ring.get_node(item)).collect();
+    print_bench_statistic(num_items, start.elapsed());
+
+    println!("Jump hashing, bulk:");
+    let start = Instant::now();
+    let bulk = jump_ring.get_nodes_u64(&workload);
+    print_bench_statistic(num_items, start.elapsed());
+    assert_eq!(per_key, bulk);
+}
+
+fn compare_algorithms(num_nodes: u64, num_items: u64, dis: KeyDistribution) {
+    println!(
+        "\nComparing algorithms ({} nodes, {} items, {})",
+        num_nodes, num_items, dis
+    );
+    let nodes: Vec<u64> = (0..num_nodes).collect();
+    let mut key_generator = Generator::new(dis);
+    let workload: Vec<u64> = key_generator.next_n(num_items);
+
+    let stats = analysis::compare_algorithms(&nodes, &workload);
+    print!("{}", analysis::format_comparison_table(&stats));
+    println!("{}", analysis::format_comparison_json(&stats));
+}
+
 fn print_vec(items: &[u64], output_filename: String) {
     let str = items.iter()
         .map(|v| v.to_string())
@@ -446,7 +475,7 @@ fn print_workload() {
     }
 }
 
-fn main() {
+fn bench_all() {
     print_workload();
 
     let nodes_list = (10..=200).step_by(10);
@@ -476,3 +505,21 @@ fn main() {
         }
     }
 }
+
+fn main() {
+    // Without an argument, runs the full sweep over all rings. An argument selects a single suite.
+    match std::env::args().nth(1).as_deref() {
+        None => bench_all(),
+        Some("frozen") => bench_frozen(NODES, ITEMS, KeyDistribution::uniform_distribution()),
+        Some("integer-keys") => {
+            bench_integer_keys(NODES, ITEMS, KeyDistribution::uniform_distribution())
+        }
+        Some("compare") => {
+            compare_algorithms(NODES, ITEMS, KeyDistribution::uniform_distribution())
+        }
+        Some(suite) => {
+            eprintln!("Unknown suite `{}`. Expected `frozen`, `integer-keys` or `compare`.", suite);
+            std::process::exit(1);
+        }
+    }
+}
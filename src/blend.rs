@@ -0,0 +1,110 @@
+use crate::util;
+use crate::HashRing;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+
+/// A router that splits points between an old ring and a new ring.
+///
+/// Each point is assigned a pseudorandom value in `[0, 1)` and it is routed through the new ring
+/// if the value is less than `fraction`. The decision is deterministic per point, so raising
+/// `fraction` only moves points from the old ring to the new ring. This allows points to be
+/// migrated gradually between two pools of nodes.
+pub struct BlendRouter<R, S, H = RandomState> {
+    old: R,
+    new: S,
+    fraction: f64,
+    hash_builder: H,
+}
+
+impl<R, S> BlendRouter<R, S, RandomState> {
+    /// Constructs a new `BlendRouter` that routes `fraction` of the points through `new`.
+    pub fn new(old: R, new: S, fraction: f64) -> Self {
+        Self::with_hasher(Default::default(), old, new, fraction)
+    }
+}
+
+impl<R, S, H> BlendRouter<R, S, H> {
+    /// Constructs a new `BlendRouter` that routes `fraction` of the points through `new` with a
+    /// specified hash builder.
+    pub fn with_hasher(hash_builder: H, old: R, new: S, fraction: f64) -> Self {
+        assert!((0.0..=1.0).contains(&fraction));
+        Self {
+            old,
+            new,
+            fraction,
+            hash_builder,
+        }
+    }
+
+    /// Returns the fraction of the points that are routed through the new ring.
+    pub fn fraction(&self) -> f64 {
+        self.fraction
+    }
+
+    /// Sets the fraction of the points that are routed through the new ring.
+    pub fn set_fraction(&mut self, fraction: f64) {
+        assert!((0.0..=1.0).contains(&fraction));
+        self.fraction = fraction;
+    }
+
+    /// Returns the old ring.
+    pub fn old_ring(&self) -> &R {
+        &self.old
+    }
+
+    /// Returns the new ring.
+    pub fn new_ring(&self) -> &S {
+        &self.new
+    }
+
+    /// Returns `true` if a point is routed through the new ring.
+    pub fn is_migrated<U>(&self, point: &U) -> bool
+    where
+        U: Hash,
+        H: BuildHasher,
+    {
+        let hash = util::gen_hash(&self.hash_builder, point);
+        self.fraction >= 1.0 || (hash as f64 / u64::MAX as f64) < self.fraction
+    }
+
+    /// Returns the node associated with a point.
+    pub fn get_node<'a, T, U>(&self, point: &U) -> &'a T
+    where
+        R: HashRing<'a, T, U>,
+        S: HashRing<'a, T, U>,
+        U: Hash,
+        H: BuildHasher,
+    {
+        if self.is_migrated(point) {
+            self.new.get_node(point)
+        } else {
+            self.old.get_node(point)
+        }
+    }
+}
+
+#[test]
+fn test_blend_router() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut old = crate::rendezvous::Ring::new();
+    let mut new = crate::rendezvous::Ring::new();
+    for node in &nodes[..5] {
+        old.insert_node(node, 1);
+    }
+    for node in &nodes[5..] {
+        new.insert_node(node, 1);
+    }
+    let mut router = BlendRouter::new(old, new, 0.0);
+    let points: Vec<u64> = (0..1000).collect();
+
+    let mut migrated = vec![false; points.len()];
+    for step in 0..=10 {
+        router.set_fraction(f64::from(step) / 10.0);
+        for (point, was_migrated) in points.iter().zip(migrated.iter_mut()) {
+            let is_migrated = *router.get_node(point) >= 5;
+            assert!(is_migrated || !*was_migrated);
+            *was_migrated = is_migrated;
+        }
+    }
+    assert!(migrated.iter().all(|is_migrated| *is_migrated));
+}
@@ -0,0 +1,195 @@
+use crate::util;
+use crate::HashRing;
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+struct Cache<'a, T> {
+    entries: HashMap<u64, (&'a T, u64, u64)>,
+    recency: BTreeMap<u64, u64>,
+    tick: u64,
+}
+
+/// A hashing ring that caches the nodes of recently looked up points.
+///
+/// Points are cached by their hash, so a lookup of a cached point is a single hash map lookup
+/// regardless of the ring. This is worthwhile for rings with expensive lookups, such as rendezvous
+/// hashing, when a small set of points is looked up frequently. The least recently used point is
+/// evicted once the cache is full.
+///
+/// The points themselves are not stored, so two points with the same hash share a cache entry, and
+/// the second point is mapped to the node of the first. The default `RandomState` is keyed
+/// randomly, which makes this as unlikely as a collision of two random 64-bit values, but unkeyed
+/// hash builders such as `util::IntegerState`, `hashers::FnvState` or `hashers::XxHashState` make
+/// collisions easy to cause, so they should not be used for the cache if the points are
+/// controlled by an adversary.
+///
+/// Modifying the ring through `ring_mut` increments a generation counter, and cached nodes from an
+/// earlier generation are not used. The cache uses interior mutability, so a `CachedRing` cannot
+/// be shared between threads.
+pub struct CachedRing<'a, T, R, H = RandomState> {
+    ring: R,
+    capacity: usize,
+    generation: u64,
+    cache: RefCell<Cache<'a, T>>,
+    hash_builder: H,
+}
+
+impl<'a, T, R> CachedRing<'a, T, R, RandomState> {
+    /// Constructs a new `CachedRing<T, R>` that caches up to `capacity` points.
+    pub fn new(ring: R, capacity: usize) -> Self {
+        Self::with_hasher(Default::default(), ring, capacity)
+    }
+}
+
+impl<'a, T, R, H> CachedRing<'a, T, R, H> {
+    /// Constructs a new `CachedRing<T, R>` that caches up to `capacity` points with a specified
+    /// hash builder.
+    pub fn with_hasher(hash_builder: H, ring: R, capacity: usize) -> Self {
+        assert!(capacity > 0);
+        Self {
+            ring,
+            capacity,
+            generation: 0,
+            cache: RefCell::new(Cache {
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                tick: 0,
+            }),
+            hash_builder,
+        }
+    }
+
+    /// Returns the underlying ring.
+    pub fn ring(&self) -> &R {
+        &self.ring
+    }
+
+    /// Returns the underlying ring so that it can be modified. This invalidates every cached
+    /// point.
+    pub fn ring_mut(&mut self) -> &mut R {
+        self.generation += 1;
+        &mut self.ring
+    }
+
+    /// Returns the number of times the ring has been modified through `ring_mut`.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the node associated with a point.
+    pub fn get_node<U>(&self, point: &U) -> &'a T
+        where
+            R: HashRing<'a, T, U>,
+            U: Hash,
+            H: BuildHasher,
+    {
+        let hash = util::gen_hash(&self.hash_builder, point);
+        let mut cache = self.cache.borrow_mut();
+        cache.tick += 1;
+        let tick = cache.tick;
+
+        let cached = cache.entries.get(&hash).cloned();
+        let node = match cached {
+            Some((node, generation, last_used)) if generation == self.generation => {
+                cache.recency.remove(&last_used);
+                node
+            }
+            Some((_, _, last_used)) => {
+                cache.recency.remove(&last_used);
+                self.ring.get_node(point)
+            }
+            None => {
+                if cache.entries.len() == self.capacity {
+                    let (oldest, oldest_hash) = cache
+                        .recency
+                        .iter()
+                        .next()
+                        .map(|(tick, hash)| (*tick, *hash))
+                        .expect("Expected non-empty cache.");
+                    cache.recency.remove(&oldest);
+                    cache.entries.remove(&oldest_hash);
+                }
+                self.ring.get_node(point)
+            }
+        };
+        cache.entries.insert(hash, (node, self.generation, tick));
+        cache.recency.insert(tick, hash);
+        node
+    }
+}
+
+impl<'a, T, U, R, H> HashRing<'a, T, U> for CachedRing<'a, T, R, H>
+    where
+        R: HashRing<'a, T, U>,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.ring.node_ids()
+    }
+
+    fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+}
+
+#[test]
+fn test_cached_ring() {
+    use crate::rendezvous;
+
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = rendezvous::Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 1);
+    }
+    let mut cached = CachedRing::new(ring, 2);
+
+    let hot_key = 0u64;
+    let node = cached.get_node(&hot_key);
+    assert_eq!(node, cached.ring().get_node(&hot_key));
+    assert_eq!(cached.get_node(&hot_key), node);
+    assert_eq!(cached.cache.borrow().entries.len(), 1);
+
+    cached.get_node(&1u64);
+    cached.get_node(&hot_key);
+    cached.get_node(&2u64);
+    let hot_key_hash = util::gen_hash(&cached.hash_builder, &hot_key);
+    let one_hash = util::gen_hash(&cached.hash_builder, &1u64);
+    assert_eq!(cached.cache.borrow().entries.len(), 2);
+    assert!(cached.cache.borrow().entries.contains_key(&hot_key_hash));
+    assert!(!cached.cache.borrow().entries.contains_key(&one_hash));
+
+    cached.ring_mut().remove_node(node);
+    assert_eq!(cached.generation(), 1);
+    let new_node = cached.get_node(&hot_key);
+    assert_ne!(new_node, node);
+    assert_eq!(new_node, cached.ring().get_node(&hot_key));
+}
+
+#[test]
+fn test_cached_ring_hash_collision() {
+    use crate::rendezvous;
+
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = rendezvous::Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 1);
+    }
+    let cached = CachedRing::with_hasher(util::ConstantState, ring, 2);
+
+    let point = (0..100u64)
+        .find(|point| cached.ring().get_node(point) != cached.ring().get_node(&0u64))
+        .unwrap();
+    let node = cached.get_node(&0u64);
+    assert_eq!(cached.get_node(&point), node);
+}
@@ -1,7 +1,10 @@
-use crate::util;
+use crate::util::{self, HashedPoint};
+use crate::{DynamicHashRing, HashRing, HashRingError, Router};
+use std::cmp::Ordering;
 use std::collections::hash_map::RandomState;
 use std::f64;
 use std::hash::{BuildHasher, Hash};
+use std::mem;
 
 /// A node with an associated weight.
 ///
@@ -25,6 +28,104 @@ impl<'a, T> Node<'a, T> {
             relative_weight: 0f64,
         }
     }
+
+    /// Returns the id of the node.
+    pub fn id(&self) -> &'a T {
+        self.id
+    }
+
+    /// Returns the weight of the node.
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+}
+
+/// The policy used to choose which node is kept when a `Ring` is constructed with several nodes
+/// that have the same id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Keep the node that appears first in the list of nodes.
+    KeepFirst,
+    /// Keep the node that appears last in the list of nodes. This matches inserting the nodes one
+    /// at a time.
+    KeepLast,
+}
+
+// The relative difference below which two weights are considered equal when computing the relative
+// weights. A relative tolerance is used because the spacing between floats grows with their
+// magnitude. Nodes are ordered by their exact weights, since a tolerance is not transitive.
+const WEIGHT_TOLERANCE: f64 = 1e-9;
+
+// Combines the hash of a node and the hash of a point with an integer mix instead of the hash
+// builder, which would construct a hasher for every node on every lookup. Both hashes are already
+// produced by the hash builder, so the scores are as unpredictable as the hashes.
+fn gen_score(node_hash: u64, point_hash: u64) -> u64 {
+    util::mix_u64(node_hash ^ point_hash)
+}
+
+fn weights_equal(a: f64, b: f64) -> bool {
+    (a - b).abs() <= f64::EPSILON.max(WEIGHT_TOLERANCE * a.abs().max(b.abs()))
+}
+
+/// A builder for a `Ring` with a hash builder, a policy for nodes that have the same id and an
+/// initial list of nodes.
+pub struct RingBuilder<'a, T, H = RandomState> {
+    nodes: Vec<Node<'a, T>>,
+    policy: DuplicatePolicy,
+    hash_builder: H,
+}
+
+impl<'a, T> RingBuilder<'a, T, RandomState> {
+    /// Constructs a new `RingBuilder<T>` for an empty ring with the default hash builder. If
+    /// several nodes have the same id, the node that was added last is kept.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<'a, T, H> RingBuilder<'a, T, H> {
+    /// Sets the hash builder of the ring.
+    pub fn hasher<G>(self, hash_builder: G) -> RingBuilder<'a, T, G> {
+        RingBuilder {
+            nodes: self.nodes,
+            policy: self.policy,
+            hash_builder,
+        }
+    }
+
+    /// Sets the policy used to choose which node is kept if several nodes have the same id.
+    pub fn duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Adds a node with a particular weight to the ring.
+    pub fn node(mut self, id: &'a T, weight: f64) -> Self {
+        self.nodes.push(Node::new(id, weight));
+        self
+    }
+
+    /// Constructs the ring.
+    pub fn build(self) -> Ring<'a, T, H>
+    where
+        T: Hash + Ord,
+        H: BuildHasher + Default,
+    {
+        Ring::with_hasher_and_policy(self.hash_builder, self.nodes, self.policy).0
+    }
+}
+
+impl<'a, T, H> Default for RingBuilder<'a, T, H>
+where
+    H: Default,
+{
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            policy: DuplicatePolicy::KeepLast,
+            hash_builder: Default::default(),
+        }
+    }
 }
 
 /// A hashing ring implemented using the Cache Array Routing Protocol.
@@ -33,6 +134,7 @@ impl<'a, T> Node<'a, T> {
 /// distribute points according to their weights.
 pub struct Ring<'a, T, H = RandomState> {
     nodes: Vec<Node<'a, T>>,
+    equal_weights: bool,
     hash_builder: H,
 }
 
@@ -69,10 +171,49 @@ impl<'a, T, H> Ring<'a, T, H> {
                 node.relative_weight /= max_relative_weight
             }
         }
+        self.equal_weights = self
+            .nodes
+            .windows(2)
+            .all(|pair| weights_equal(pair[0].weight, pair[1].weight));
+    }
+
+    // Orders nodes by weight, and nodes with equal weights by id.
+    fn cmp_nodes(n: &Node<'a, T>, m: &Node<'a, T>) -> Ordering
+    where
+        T: Ord,
+    {
+        n.weight.total_cmp(&m.weight).then_with(|| n.id.cmp(m.id))
+    }
+
+    // Sorts the nodes by weight, and nodes with equal weights by id.
+    fn sort_nodes(nodes: &mut [Node<'a, T>])
+    where
+        T: Ord,
+    {
+        nodes.sort_by(Self::cmp_nodes);
     }
 
     /// Constructs a new, empty `Ring<T>` with a specified hash builder.
-    pub fn with_hasher(hash_builder: H, mut nodes: Vec<Node<'a, T>>) -> Self
+    ///
+    /// If several nodes have the same id, the node that appears last is kept. Use
+    /// `with_hasher_and_policy` to choose which node is kept and to find out which nodes were
+    /// dropped.
+    pub fn with_hasher(hash_builder: H, nodes: Vec<Node<'a, T>>) -> Self
+    where
+        T: Hash + Ord,
+        H: BuildHasher + Default,
+    {
+        Self::with_hasher_and_policy(hash_builder, nodes, DuplicatePolicy::KeepLast).0
+    }
+
+    /// Constructs a new, empty `Ring<T>` with a specified hash builder and a policy for nodes that
+    /// have the same id. Returns the ring along with the nodes that were dropped because another
+    /// node with the same id was kept.
+    pub fn with_hasher_and_policy(
+        hash_builder: H,
+        mut nodes: Vec<Node<'a, T>>,
+        policy: DuplicatePolicy,
+    ) -> (Self, Vec<Node<'a, T>>)
     where
         T: Hash + Ord,
         H: BuildHasher + Default,
@@ -80,24 +221,27 @@ impl<'a, T, H> Ring<'a, T, H> {
         for node in &mut nodes {
             node.hash = util::gen_hash(&hash_builder, node.id);
         }
-        nodes.reverse();
+        if policy == DuplicatePolicy::KeepLast {
+            nodes.reverse();
+        }
         nodes.sort_by_key(|node| node.id);
-        nodes.dedup_by_key(|node| node.id);
-        nodes.sort_by(|n, m| {
-            if (n.weight - m.weight).abs() < f64::EPSILON {
-                n.id.cmp(m.id)
-            } else {
-                n.weight
-                    .partial_cmp(&m.weight)
-                    .expect("Expected all non-NaN floats.")
+        let mut kept: Vec<Node<'a, T>> = Vec::with_capacity(nodes.len());
+        let mut dropped = Vec::new();
+        for node in nodes {
+            match kept.last() {
+                Some(last) if last.id == node.id => dropped.push(node),
+                _ => kept.push(node),
             }
-        });
+        }
+        let mut nodes = kept;
+        Self::sort_nodes(&mut nodes);
         let mut ret = Self {
             nodes,
+            equal_weights: false,
             hash_builder,
         };
         ret.rebalance();
-        ret
+        (ret, dropped)
     }
 
     /// Inserts a node into the ring with a particular weight.
@@ -116,15 +260,7 @@ impl<'a, T, H> Ring<'a, T, H> {
         } else {
             self.nodes.push(new_node);
         }
-        self.nodes.sort_by(|n, m| {
-            if (n.weight - m.weight).abs() < f64::EPSILON {
-                n.id.cmp(m.id)
-            } else {
-                n.weight
-                    .partial_cmp(&m.weight)
-                    .expect("Expected all non-NaN floats.")
-            }
-        });
+        Self::sort_nodes(&mut self.nodes);
         self.rebalance();
     }
 
@@ -139,6 +275,31 @@ impl<'a, T, H> Ring<'a, T, H> {
         }
     }
 
+    /// Sets the weight of a node. Nothing happens if the node is not in the ring.
+    ///
+    /// Only the updated node is moved to keep the nodes ordered by weight, so this is cheaper than
+    /// removing the node and inserting it again, but the result is the same.
+    pub fn update_weight(&mut self, id: &T, weight: f64)
+    where
+        T: Ord,
+    {
+        if let Some(index) = self.index_of(id) {
+            let mut node = self.nodes.remove(index);
+            node.weight = weight;
+            let index = self
+                .nodes
+                .partition_point(|other| Self::cmp_nodes(other, &node) == Ordering::Less);
+            self.nodes.insert(index, node);
+            self.rebalance();
+        }
+    }
+
+    /// Removes every node from the ring. The hash builder of the ring is kept.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.rebalance();
+    }
+
     /// Returns the node associated with a point.
     pub fn get_node<U>(&self, point: &U) -> &'a T
     where
@@ -146,25 +307,204 @@ impl<'a, T, H> Ring<'a, T, H> {
         U: Hash,
         H: BuildHasher,
     {
-        let point_hash = util::gen_hash(&self.hash_builder, point);
+        self.try_get_node(point).expect("Expected non-empty ring.")
+    }
+
+    /// Returns the node associated with a point, or `HashRingError::EmptyRing` if the ring is
+    /// empty.
+    pub fn try_get_node<U>(&self, point: &U) -> Result<&'a T, HashRingError>
+    where
+        T: Ord,
+        U: Hash,
+        H: BuildHasher,
+    {
+        if self.nodes.is_empty() {
+            Err(HashRingError::EmptyRing)
+        } else {
+            Ok(self.get_node_prehashed(&util::hash_point(&self.hash_builder, point)))
+        }
+    }
+
+    /// Returns the node associated with a point that was hashed by `util::hash_point` with the
+    /// same hash builder as the ring.
+    pub fn get_node_prehashed(&self, point: &HashedPoint) -> &'a T
+    where
+        T: Ord,
+        H: BuildHasher,
+    {
+        self.nodes[self.get_node_index_prehashed(point.value())].id
+    }
+
+    /// Returns the nodes associated with a slice of points. The result is the same as calling
+    /// `get_node` for every point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring is empty and `points` is not empty.
+    pub fn get_node_batch<U>(&self, points: &[U]) -> Vec<&'a T>
+    where
+        T: Ord,
+        U: Hash,
+        H: BuildHasher,
+    {
+        points
+            .iter()
+            .map(|point| {
+                let point_hash = util::gen_hash(&self.hash_builder, point);
+                self.nodes[self.get_node_index_prehashed(point_hash)].id
+            })
+            .collect()
+    }
+
+    /// Returns the index of the node associated with a point.
+    ///
+    /// The index is a position in the nodes of the ring, which are ordered by weight and then by
+    /// id. Inserting or removing a node can change the index of every other node, so indices
+    /// should be looked up again with `index_of` after the ring is modified.
+    pub fn get_node_index<U>(&self, point: &U) -> usize
+    where
+        T: Ord,
+        U: Hash,
+        H: BuildHasher,
+    {
+        self.get_node_index_prehashed(util::gen_hash(&self.hash_builder, point))
+    }
+
+    /// Returns the index of a node, or `None` if the node is not in the ring.
+    pub fn index_of(&self, id: &T) -> Option<usize>
+    where
+        T: Eq,
+    {
+        self.nodes.iter().position(|node| node.id == id)
+    }
+
+    fn get_node_index_prehashed(&self, point_hash: u64) -> usize
+    where
+        T: Ord,
+        H: BuildHasher,
+    {
+        if self.equal_weights {
+            self.get_unweighted_node(point_hash)
+        } else {
+            self.get_weighted_node(point_hash)
+        }
+    }
+
+    // When all nodes have the same weight, all relative weights are one and the scores can be
+    // compared as integers.
+    fn get_unweighted_node(&self, point_hash: u64) -> usize
+    where
+        T: Ord,
+        H: BuildHasher,
+    {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                (gen_score(node.hash, point_hash), node.id, index)
+            })
+            .max()
+            .expect("Expected non-empty ring.")
+            .2
+    }
+
+    fn get_weighted_node(&self, point_hash: u64) -> usize
+    where
+        T: Ord,
+        H: BuildHasher,
+    {
         self.nodes
             .iter()
-            .map(|node| {
+            .enumerate()
+            .map(|(index, node)| {
                 (
-                    util::combine_hash(&self.hash_builder, node.hash, point_hash) as f64
-                        * node.relative_weight,
+                    gen_score(node.hash, point_hash) as f64 * node.relative_weight,
                     node.id,
+                    index,
                 )
             })
             .max_by(|n, m| {
-                if n == m {
+                if (n.0, n.1) == (m.0, m.1) {
                     n.1.cmp(m.1)
                 } else {
                     n.0.partial_cmp(&m.0).expect("Expected all non-NaN floats.")
                 }
             })
             .expect("Expected non-empty ring.")
-            .1
+            .2
+    }
+
+    /// Returns the sum of the weights of the nodes in the ring.
+    pub fn total_weight(&self) -> f64 {
+        self.nodes.iter().map(|node| node.weight).sum()
+    }
+
+    /// Returns the weight of a node divided by the total weight of the ring, which is the expected
+    /// fraction of points mapped to the node, or `None` if the node is not in the ring.
+    ///
+    /// Unlike the relative weights used to score the nodes, the shares of the nodes are
+    /// proportional to their weights.
+    pub fn weight_share(&self, id: &T) -> Option<f64>
+    where
+        T: Eq,
+    {
+        self.index_of(id)
+            .map(|index| self.nodes[index].weight / self.total_weight())
+    }
+
+    /// Returns the id and relative weight of every node, ordered by weight and then by id.
+    ///
+    /// The score of a node for a point is the hash of the node and the point multiplied by its
+    /// relative weight. The relative weights are scaled so that the greatest is one.
+    pub fn relative_weights(&self) -> Vec<(&'a T, f64)> {
+        self.nodes
+            .iter()
+            .map(|node| (node.id, node.relative_weight))
+            .collect()
+    }
+
+    /// Returns the expected fraction of points mapped to a node, computed from the relative
+    /// weights of the nodes, or `None` if the node is not in the ring. The expected loads of the
+    /// nodes sum to one.
+    ///
+    /// If the relative weights are correct, the expected load of a node is equal to its
+    /// `weight_share`.
+    pub fn expected_load(&self, id: &T) -> Option<f64>
+    where
+        T: Eq,
+    {
+        self.index_of(id).map(|index| self.get_expected_load(index))
+    }
+
+    // The node at `index` wins a point if its score `s` is greater than the scores of the other
+    // nodes. Scores are uniform in `[0, x_j]`, where `x_j` is the relative weight of node `j`, so
+    // the probability is the integral of `prod_{j != index} min(s / x_j, 1)` over `[0, x_index]`,
+    // divided by `x_index`. The nodes are ordered by relative weight, so the integral is split at
+    // the relative weights of the lighter nodes.
+    fn get_expected_load(&self, index: usize) -> f64 {
+        let len = self.nodes.len();
+        let weight = self.nodes[index].relative_weight;
+        let mut load = 0f64;
+        let mut start = 0f64;
+        for i in 0..=index {
+            let end = self.nodes[i].relative_weight;
+            let exponent = (len - i) as i32;
+            let product: f64 = self.nodes[i..]
+                .iter()
+                .map(|node| node.relative_weight)
+                .product::<f64>()
+                / weight;
+            load += (end.powi(exponent) - start.powi(exponent)) / exponent as f64 / product;
+            start = end;
+        }
+        load / weight
+    }
+
+    /// Returns an estimate of the number of bytes used by the ring: the ring itself and the
+    /// allocated capacity of its list of nodes, each of which stores its weight, relative weight
+    /// and hash.
+    pub fn memory_footprint(&self) -> usize {
+        mem::size_of::<Self>() + self.nodes.capacity() * mem::size_of::<Node<'a, T>>()
     }
 
     /// Returns the number of nodes in the ring.
@@ -176,4 +516,332 @@ impl<'a, T, H> Ring<'a, T, H> {
     pub fn is_empty(&self) -> bool {
         self.nodes.is_empty()
     }
-}
\ No newline at end of file
+}
+
+impl<'a, T, U, H> HashRing<'a, T, U> for Ring<'a, T, H>
+where
+    T: Ord,
+    U: Hash,
+    H: BuildHasher,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn try_get_node(&self, point: &U) -> Result<&'a T, HashRingError> {
+        self.try_get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.nodes.iter().map(|node| node.id).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<'a, T, U, H> DynamicHashRing<'a, T, U> for Ring<'a, T, H>
+where
+    T: Ord,
+    U: Hash,
+    H: BuildHasher,
+{
+    fn remove_node(&mut self, id: &T) {
+        self.remove_node(id)
+    }
+}
+
+impl<'a, T, H> Router for Ring<'a, T, H>
+where
+    T: Ord,
+    H: BuildHasher,
+{
+    type Output = &'a T;
+
+    fn route<K>(&self, key: &K) -> &'a T
+    where
+        K: Hash,
+    {
+        self.get_node(key)
+    }
+}
+
+#[test]
+fn test_equal_weights() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let ring = Ring::new(nodes.iter().map(|node| Node::new(node, 1.0)).collect());
+
+    assert!(ring.equal_weights);
+    for point in 0..1000u64 {
+        let point_hash = util::gen_hash(&ring.hash_builder, &point);
+        assert_eq!(ring.get_node_index(&point), ring.get_weighted_node(point_hash));
+    }
+}
+
+#[test]
+fn test_unequal_weights() {
+    let nodes: Vec<u64> = (0..2).collect();
+    let mut ring = Ring::new(vec![Node::new(&nodes[0], 1.0), Node::new(&nodes[1], 1.0)]);
+    ring.insert_node(Node::new(&nodes[1], 3.0));
+
+    assert!(!ring.equal_weights);
+    let heavy = (0..10_000u64)
+        .filter(|point| *ring.get_node(point) == nodes[1])
+        .count();
+    assert!(heavy > 6_000);
+}
+
+#[test]
+fn test_duplicate_policy() {
+    let nodes = || vec![Node::new(&0, 1.0), Node::new(&1, 2.0), Node::new(&0, 3.0)];
+
+    let (ring, dropped) =
+        Ring::with_hasher_and_policy(RandomState::new(), nodes(), DuplicatePolicy::KeepFirst);
+    assert_eq!(ring.len(), 2);
+    assert_eq!(dropped.len(), 1);
+    assert_eq!((dropped[0].id(), dropped[0].weight()), (&0, 3.0));
+
+    let (ring, dropped) =
+        Ring::with_hasher_and_policy(RandomState::new(), nodes(), DuplicatePolicy::KeepLast);
+    assert_eq!(ring.len(), 2);
+    assert_eq!(dropped.len(), 1);
+    assert_eq!((dropped[0].id(), dropped[0].weight()), (&0, 1.0));
+}
+
+#[test]
+fn test_get_node_index() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let ring = Ring::new(
+        nodes
+            .iter()
+            .map(|node| Node::new(node, (10 - node) as f64))
+            .collect(),
+    );
+
+    assert_eq!(ring.index_of(&10), None);
+    for point in 0..1000u64 {
+        let index = ring.get_node_index(&point);
+        assert!(index < ring.len());
+        assert_eq!(ring.index_of(ring.get_node(&point)), Some(index));
+    }
+}
+
+#[test]
+fn test_weight_share() {
+    let nodes: Vec<u64> = (0..4).collect();
+    let ring = Ring::new(
+        nodes
+            .iter()
+            .map(|node| Node::new(node, (node + 1) as f64))
+            .collect(),
+    );
+
+    assert!((ring.total_weight() - 10.0).abs() < f64::EPSILON);
+    assert!((ring.weight_share(&nodes[3]).unwrap() - 0.4).abs() < f64::EPSILON);
+    assert_eq!(ring.weight_share(&4), None);
+    let total_share: f64 = nodes.iter().map(|node| ring.weight_share(node).unwrap()).sum();
+    assert!((total_share - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_large_weights() {
+    let nodes: Vec<u64> = (0..2).collect();
+    let ring = Ring::new(vec![
+        Node::new(&nodes[1], 1_000_000.0),
+        Node::new(&nodes[0], 1_000_000.000_1),
+    ]);
+    let other = Ring::new(vec![
+        Node::new(&nodes[0], 1_000_000.000_1),
+        Node::new(&nodes[1], 1_000_000.0),
+    ]);
+
+    assert!(ring.equal_weights);
+    assert_eq!(ring.index_of(&nodes[0]), Some(1));
+    assert_eq!(other.index_of(&nodes[0]), Some(1));
+    assert!(weights_equal(1.0, 1.0 + f64::EPSILON / 2.0));
+    assert!(!weights_equal(1_000_000.0, 1_000_000.01));
+}
+
+#[test]
+fn test_clear() {
+    let nodes = [0, 1, 2];
+    let mut ring = Ring::new(vec![
+        Node::new(&nodes[0], 1.0),
+        Node::new(&nodes[1], 2.0),
+        Node::new(&nodes[2], 3.0),
+    ]);
+    ring.clear();
+    assert!(ring.is_empty());
+    assert_eq!(ring.len(), 0);
+
+    ring.insert_node(Node::new(&nodes[0], 1.0));
+    assert_eq!(ring.get_node(&0), &nodes[0]);
+}
+
+#[test]
+fn test_try_get_node() {
+    let node = 0;
+    let mut ring = Ring::new(vec![]);
+    assert_eq!(ring.try_get_node(&0), Err(HashRingError::EmptyRing));
+
+    ring.insert_node(Node::new(&node, 1.0));
+    assert_eq!(ring.try_get_node(&0), Ok(&node));
+}
+
+#[test]
+fn test_get_node_batch() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let points: Vec<u64> = (0..1000).collect();
+    let ring = Ring::new(
+        nodes
+            .iter()
+            .map(|node| Node::new(node, *node as f64 + 1.0))
+            .collect(),
+    );
+
+    let expected: Vec<&u64> = points.iter().map(|point| ring.get_node(point)).collect();
+    assert_eq!(ring.get_node_batch(&points), expected);
+}
+
+#[test]
+fn test_expected_load() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let ring = Ring::new(nodes.iter().map(|node| Node::new(node, 1.0)).collect());
+    for node in &nodes {
+        assert!((ring.expected_load(node).unwrap() - 0.1).abs() < 1e-9);
+    }
+    assert_eq!(ring.expected_load(&10), None);
+
+    let ring = Ring::new(nodes.iter().map(|node| Node::new(node, (node + 1) as f64)).collect());
+    let relative_weights = ring.relative_weights();
+    assert_eq!(relative_weights.len(), nodes.len());
+    assert_eq!(relative_weights[9], (&nodes[9], 1.0));
+    let mut total = 0.0;
+    for node in &nodes {
+        let load = ring.expected_load(node).unwrap();
+        assert!((load - ring.weight_share(node).unwrap()).abs() < 1e-9);
+        total += load;
+    }
+    assert!((total - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_update_weight() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let hash_builder = RandomState::new();
+    let new_nodes = || nodes.iter().map(|node| Node::new(node, (node % 3 + 1) as f64)).collect();
+    let mut ring = Ring::with_hasher(hash_builder.clone(), new_nodes());
+    let mut other = Ring::with_hasher(hash_builder, new_nodes());
+
+    for (node, weight) in &[(&nodes[0], 5.0), (&nodes[4], 0.5), (&nodes[7], 2.0)] {
+        ring.update_weight(node, *weight);
+        other.remove_node(node);
+        other.insert_node(Node::new(node, *weight));
+    }
+    ring.update_weight(&10, 1.0);
+
+    assert_eq!(ring.relative_weights(), other.relative_weights());
+    for point in 0..1000u64 {
+        assert_eq!(ring.get_node(&point), other.get_node(&point));
+    }
+}
+
+#[test]
+fn test_update_weight_nearly_equal() {
+    let nodes: Vec<u64> = (0..3).collect();
+    let mut ring = Ring::new(vec![
+        Node::new(&nodes[0], 1.0),
+        Node::new(&nodes[1], 1.0 + 1e-12),
+        Node::new(&nodes[2], 1.0 + 2e-12),
+    ]);
+    ring.update_weight(&nodes[0], 1.0 + 3e-12);
+    ring.update_weight(&nodes[2], 1.0 - 1e-12);
+
+    let weights: Vec<f64> = ring.nodes.iter().map(|node| node.weight).collect();
+    assert!(weights.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(ring.index_of(&nodes[2]), Some(0));
+    assert_eq!(ring.index_of(&nodes[0]), Some(2));
+}
+
+#[test]
+fn test_hashes_per_lookup() {
+    use std::cell::Cell;
+    use std::collections::hash_map::DefaultHasher;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct CountingState(Rc<Cell<usize>>);
+
+    impl BuildHasher for CountingState {
+        type Hasher = DefaultHasher;
+
+        fn build_hasher(&self) -> DefaultHasher {
+            self.0.set(self.0.get() + 1);
+            DefaultHasher::new()
+        }
+    }
+
+    let nodes: Vec<u64> = (0..100).collect();
+    let hash_builder = CountingState::default();
+    let ring = Ring::with_hasher(
+        hash_builder.clone(),
+        nodes.iter().map(|node| Node::new(node, (node % 3 + 1) as f64)).collect(),
+    );
+
+    hash_builder.0.set(0);
+    for point in 0..1000u64 {
+        ring.get_node(&point);
+    }
+    assert_eq!(hash_builder.0.get(), 1000);
+}
+
+#[test]
+fn test_distribution() {
+    let nodes: Vec<u64> = (0..5).collect();
+    let points = 100_000u64;
+    for weights in &[[1.0; 5], [1.0, 2.0, 3.0, 4.0, 5.0]] {
+        let ring = Ring::new(
+            nodes
+                .iter()
+                .zip(weights)
+                .map(|(node, weight)| Node::new(node, *weight))
+                .collect(),
+        );
+        let mut counts = [0u64; 5];
+        for point in 0..points {
+            counts[*ring.get_node(&point) as usize] += 1;
+        }
+        for node in &nodes {
+            let share = counts[*node as usize] as f64 / points as f64;
+            assert!((share - ring.weight_share(node).unwrap()).abs() < 0.01);
+        }
+    }
+}
+
+#[test]
+fn test_ring_builder() {
+    let nodes = [0, 1, 2];
+    let hash_builder = RandomState::new();
+    let built = RingBuilder::new()
+        .hasher(hash_builder.clone())
+        .duplicate_policy(DuplicatePolicy::KeepFirst)
+        .node(&nodes[0], 1.0)
+        .node(&nodes[1], 2.0)
+        .node(&nodes[2], 3.0)
+        .node(&nodes[0], 4.0)
+        .build();
+    let ring = Ring::with_hasher(
+        hash_builder,
+        vec![Node::new(&nodes[0], 1.0), Node::new(&nodes[1], 2.0), Node::new(&nodes[2], 3.0)],
+    );
+
+    assert_eq!(built.relative_weights(), ring.relative_weights());
+    for point in 0..1000 {
+        assert_eq!(built.get_node(&point), ring.get_node(&point));
+    }
+}
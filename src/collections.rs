@@ -0,0 +1,10 @@
+// Hash maps and sets that are available with and without the standard library. Without it, they
+// are provided by `hashbrown` and use `SipState`, since there is no source of random keys.
+#[cfg(feature = "std")]
+pub use std::collections::{HashMap, HashSet};
+
+#[cfg(not(feature = "std"))]
+pub type HashMap<K, V> = hashbrown::HashMap<K, V, crate::util::SipState>;
+
+#[cfg(not(feature = "std"))]
+pub type HashSet<T> = hashbrown::HashSet<T, crate::util::SipState>;
@@ -0,0 +1,125 @@
+use crate::{HashRing, HashRingError};
+use arc_swap::ArcSwap;
+use std::sync::Arc;
+
+/// A hashing ring that can be read by many threads while it is replaced by a writer.
+///
+/// The ring is held in an atomically swappable `Arc`. Lookups load the current ring without
+/// locking, and writers build a new ring and swap it in, so a lookup never observes a ring that is
+/// being modified. A reader that needs several lookups to agree should take a `snapshot` and look
+/// up every point in it.
+pub struct ConcurrentRing<R> {
+    ring: ArcSwap<R>,
+}
+
+impl<R> ConcurrentRing<R> {
+    /// Constructs a new `ConcurrentRing<R>` that initially holds `ring`.
+    pub fn new(ring: R) -> Self {
+        Self {
+            ring: ArcSwap::from_pointee(ring),
+        }
+    }
+
+    /// Returns the current ring. The snapshot is not affected by later calls to `store` or
+    /// `update`.
+    pub fn snapshot(&self) -> Arc<R> {
+        self.ring.load_full()
+    }
+
+    /// Replaces the ring and returns the previous ring.
+    pub fn store(&self, ring: R) -> Arc<R> {
+        self.ring.swap(Arc::new(ring))
+    }
+
+    /// Replaces the ring with the ring returned by `f`, which is called with the current ring.
+    ///
+    /// If another writer replaces the ring concurrently, `f` is called again with the new ring, so
+    /// no update is lost.
+    pub fn update<F>(&self, f: F)
+        where
+            F: Fn(&R) -> R,
+    {
+        self.ring.rcu(|ring| f(ring));
+    }
+
+    /// Returns the node associated with a point in the current ring.
+    pub fn get_node<'a, T, U>(&self, point: &U) -> &'a T
+        where
+            R: HashRing<'a, T, U>,
+    {
+        self.ring.load().get_node(point)
+    }
+}
+
+impl<'a, T, U, R> HashRing<'a, T, U> for ConcurrentRing<R>
+    where
+        R: HashRing<'a, T, U>,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn try_get_node(&self, point: &U) -> Result<&'a T, HashRingError> {
+        self.ring.load().try_get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.ring.load().node_ids()
+    }
+
+    fn len(&self) -> usize {
+        self.ring.load().len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ring.load().is_empty()
+    }
+}
+
+#[test]
+fn test_concurrent_ring() {
+    use crate::rendezvous::Ring;
+    use std::collections::hash_map::RandomState;
+    use std::thread;
+
+    let nodes: Vec<u64> = (0..10).collect();
+    let points: Vec<u64> = (0..1000).collect();
+    let hash_builder = RandomState::new();
+    let new_ring = |len: usize| {
+        let mut ring = Ring::with_hasher(hash_builder.clone());
+        for node in &nodes[..len] {
+            ring.insert_node(node, 1);
+        }
+        ring
+    };
+    let expected = |len: usize| {
+        let ring = new_ring(len);
+        points.iter().map(|point| ring.get_node(point)).collect::<Vec<_>>()
+    };
+    let expected = [expected(5), expected(10)];
+    let ring = ConcurrentRing::new(new_ring(5));
+
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..20 {
+                    let snapshot = ring.snapshot();
+                    let index = if snapshot.len() == 5 { 0 } else { 1 };
+                    for (point, node) in points.iter().zip(&expected[index]) {
+                        assert_eq!(snapshot.get_node(point), *node);
+                        assert!(nodes.contains(ring.get_node(point)));
+                    }
+                }
+            });
+        }
+        scope.spawn(|| {
+            for len in &[10, 5, 10] {
+                ring.store(new_ring(*len));
+            }
+        });
+    });
+
+    assert_eq!(ring.snapshot().len(), 10);
+    ring.update(|ring| new_ring(ring.len() - 1));
+    assert_eq!(ring.snapshot().len(), 9);
+}
@@ -1,9 +1,73 @@
-use crate::util;
+use crate::util::{self, HashedPoint};
+use crate::{CowHashRing, DynamicHashRing, HashRing, HashRingError, Router};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::collections::{HashMap, HashSet};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cmp;
+use core::hash::{BuildHasher, Hash};
+use core::iter::Iterator;
+use core::mem;
+#[cfg(test)]
+use alloc::vec;
+#[cfg(all(test, feature = "std"))]
 use std::collections::hash_map::RandomState;
-use std::collections::{BTreeMap, HashMap, HashSet};
-use std::hash::{BuildHasher, Hash};
-use std::iter::Iterator;
-use std::vec::Vec;
+
+fn gen_replica_hash<T, H>(hash_builder: &H, id: &T, index: usize) -> u64
+    where
+        T: Hash,
+        H: BuildHasher,
+{
+    util::combine_hash(
+        hash_builder,
+        util::gen_hash(hash_builder, id),
+        util::gen_hash(hash_builder, &index),
+    )
+}
+
+// Returns the number of bytes used by a position of type `K` and its nodes. A `BTreeMap` stores
+// its keys and values in separate arrays, so a key is not padded to the alignment of its value.
+fn gen_slot_footprint<K, T>(ids: &Vec<&T>) -> usize {
+    mem::size_of::<K>() + mem::size_of::<Vec<&T>>() + ids.capacity() * mem::size_of::<&T>()
+}
+
+// Returns the fraction of the points that a node with positions `hashes` would receive from a ring
+// whose other positions are `others`, in ascending order. A position of the node that is also in
+// `others` receives no points.
+fn gen_load(others: &[u64], hashes: &[u64]) -> f64 {
+    let mut positions: Vec<(u64, bool)> = others.iter().map(|hash| (*hash, false)).collect();
+    positions.extend(
+        hashes.iter().filter(|hash| others.binary_search(hash).is_err()).map(|hash| (*hash, true)),
+    );
+    positions.sort_unstable();
+    positions.dedup();
+
+    let space = u64::MAX as f64 + 1.0;
+    let mut prev = positions[positions.len() - 1].0;
+    let mut load = 0.0;
+    for (hash, is_node) in positions {
+        if is_node {
+            load += hash.wrapping_sub(prev) as f64 / space;
+        }
+        prev = hash;
+    }
+    load
+}
+
+// Rounds a non-negative value up to an integer. `f64::ceil` is not available without the standard
+// library.
+fn ceil(value: f64) -> u64 {
+    let truncated = value as u64;
+    if (truncated as f64) < value {
+        truncated + 1
+    } else {
+        truncated
+    }
+}
 
 /// A hashing ring implemented using consistent hashing.
 ///
@@ -12,13 +76,33 @@ use std::vec::Vec;
 /// replica number. A point is also represented as a pseudorandom value and it is mapped to the
 /// node with the smallest value that is greater than or equal to the point's value. If such a
 /// node does not exist, then the point maps to the node with the smallest value.
-pub struct Ring<'a, T, H = RandomState> {
-    nodes: BTreeMap<u64, &'a T>,
-    replicas: HashMap<&'a T, usize>,
+///
+/// If several replicas are mapped to the same value, they are all kept, and the value is owned by
+/// the replica that was inserted first until it is removed.
+pub struct Ring<'a, T, H = util::DefaultState> {
+    nodes: BTreeMap<u64, Vec<&'a T>>,
+    // The replica indices of each node in ascending order.
+    replicas: HashMap<&'a T, Vec<usize>>,
+    bounded: Option<Box<BoundedLoads<'a, T>>>,
     hash_builder: H,
 }
 
-impl<'a, T> Ring<'a, T, RandomState> {
+// The points assigned by `Ring::get_node_bounded`, which are only allocated once it is used.
+struct BoundedLoads<'a, T> {
+    loads: HashMap<&'a T, u64>,
+    assignments: HashMap<u64, &'a T>,
+}
+
+impl<'a, T> BoundedLoads<'a, T> {
+    fn load_of(&self, id: &T) -> u64
+        where
+            T: Hash + Eq,
+    {
+        self.loads.get(id).map_or(0, |load| *load)
+    }
+}
+
+impl<'a, T> Ring<'a, T, util::DefaultState> {
     /// Constructs a new, empty `Ring<T>`.
     pub fn new() -> Self
         where
@@ -26,6 +110,44 @@ impl<'a, T> Ring<'a, T, RandomState> {
     {
         Self::default()
     }
+
+    /// Constructs a new `Ring<T>` with the specified nodes and numbers of replicas.
+    pub fn from_nodes<I>(nodes: I) -> Self
+        where
+            T: Hash + Eq,
+            I: IntoIterator<Item = (&'a T, usize)>,
+    {
+        Self::from_nodes_with_hasher(Default::default(), nodes)
+    }
+}
+
+#[cfg(all(feature = "std", feature = "fast-hash"))]
+impl<'a, T> Ring<'a, T, crate::hashers::XxHashState> {
+    /// Constructs a new, empty `Ring<T>` that hashes with 64-bit xxHash, which is faster than the
+    /// default hash builder but is not keyed.
+    pub fn with_xxhash() -> Self
+        where
+            T: Hash + Eq,
+    {
+        Self::default()
+    }
+}
+
+impl<'a, T> Ring<'a, T, util::IntegerState> {
+    /// Returns the nodes associated with a slice of `u64` keys.
+    ///
+    /// The keys are mixed in a single pass before the ring is searched, which avoids constructing
+    /// a hasher for every key. The result is the same as calling `get_node` for every key.
+    pub fn get_nodes_u64(&self, keys: &[u64]) -> Vec<&'a T> {
+        let hashes: Vec<u64> = keys.iter().map(|key| util::mix_u64(*key)).collect();
+        hashes
+            .into_iter()
+            .map(|hash| match self.get_next_node(hash) {
+                Some(node) => node,
+                None => panic!("Error: empty ring."),
+            })
+            .collect()
+    }
 }
 
 impl<'a, T, H> Ring<'a, T, H> {
@@ -37,38 +159,271 @@ impl<'a, T, H> Ring<'a, T, H> {
     {
         Self {
             nodes: BTreeMap::new(),
-            replicas: HashMap::new(),
+            replicas: HashMap::default(),
+            bounded: None,
             hash_builder,
         }
     }
 
-    fn get_next_node(&self, hash: u64) -> Option<&T> {
+    /// Constructs a new `Ring<T>` with the specified nodes and numbers of replicas and a specified
+    /// hash builder.
+    pub fn from_nodes_with_hasher<I>(hash_builder: H, nodes: I) -> Self
+        where
+            T: Hash + Eq,
+            H: BuildHasher + Default,
+            I: IntoIterator<Item = (&'a T, usize)>,
+    {
+        let mut ring = Self::with_hasher(hash_builder);
+        for (id, replicas) in nodes {
+            ring.insert_node(id, replicas);
+        }
+        ring
+    }
+
+    fn get_replica_hash(&self, id: &T, index: usize) -> u64
+        where
+            T: Hash,
+            H: BuildHasher,
+    {
+        gen_replica_hash(&self.hash_builder, id, index)
+    }
+
+    fn get_next_node(&self, hash: u64) -> Option<&'a T> {
+        self.get_next_position(hash).map(|entry| entry.0)
+    }
+
+    fn get_next_position(&self, hash: u64) -> Option<(&'a T, u64)> {
         self.nodes
             .range(hash..)
             .next()
             .or_else(|| self.nodes.iter().next())
-            .map(|entry| *entry.1)
+            .map(|entry| (entry.1[0], *entry.0))
     }
 
     /// Inserts a node into the ring with a number of replicas.
     ///
     /// Increasing the number of replicas will increase the number of expected points mapped to the
     /// node. For example, a node with three replicas will receive approximately three times more
-    /// points than a node with one replica.
+    /// points than a node with one replica. If the node is already in the ring, its replicas are
+    /// replaced.
     pub fn insert_node(&mut self, id: &'a T, replicas: usize)
         where
             T: Hash + Eq,
             H: BuildHasher,
     {
+        self.remove_node(id);
         for i in 0..replicas {
-            let hash = util::combine_hash(
-                &self.hash_builder,
-                util::gen_hash(&self.hash_builder, id),
-                util::gen_hash(&self.hash_builder, &i),
-            );
-            self.nodes.insert(hash, id);
+            self.insert_slot(id, i);
         }
-        self.replicas.insert(id, replicas);
+        self.replicas.insert(id, (0..replicas).collect());
+    }
+
+    fn insert_slot(&mut self, id: &'a T, replica_index: usize)
+        where
+            T: Hash,
+            H: BuildHasher,
+    {
+        let hash = self.get_replica_hash(id, replica_index);
+        self.nodes.entry(hash).or_default().push(id);
+    }
+
+    /// Changes the number of replicas of a node. If the node is not in the ring, it is inserted.
+    ///
+    /// Only the positions of the added or removed replicas are changed, so points that are not
+    /// mapped to those positions keep their nodes. Replicas with the highest indices are removed
+    /// first, and the lowest unused indices are added first. Updating a node to zero replicas
+    /// removes it from the ring.
+    pub fn update_replicas(&mut self, id: &'a T, replicas: usize)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        let mut indices = self.replicas.remove(id).unwrap_or_default();
+        indices.sort_unstable();
+        for i in indices.split_off(cmp::min(replicas, indices.len())) {
+            self.remove_slot(id, i);
+        }
+
+        let mut added = Vec::new();
+        let mut i = 0;
+        while indices.len() + added.len() < replicas {
+            if indices.binary_search(&i).is_err() {
+                self.insert_slot(id, i);
+                added.push(i);
+            }
+            i += 1;
+        }
+        indices.extend(added);
+        indices.sort_unstable();
+
+        if indices.is_empty() {
+            self.release_node(id);
+        } else {
+            self.replicas.insert(id, indices);
+        }
+    }
+
+    /// Inserts a node into the ring with a number of replicas derived from its capacity.
+    ///
+    /// The number of replicas is `capacity / bytes_per_replica` rounded to the nearest integer, so
+    /// points are distributed proportionally to the capacity of the nodes. Every node receives at
+    /// least one replica, regardless of its capacity.
+    pub fn insert_node_by_capacity(&mut self, id: &'a T, capacity: u64, bytes_per_replica: u64)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        assert!(bytes_per_replica > 0);
+        let remainder = capacity % bytes_per_replica;
+        let replicas = capacity / bytes_per_replica
+            + u64::from(remainder >= bytes_per_replica - remainder);
+        self.insert_node(id, cmp::max(replicas as usize, 1));
+    }
+
+    /// Inserts a node into the ring with the number of replicas that gives it approximately a
+    /// target fraction of the points, according to `estimated_load`. If the node is already in the
+    /// ring, its number of replicas is updated.
+    ///
+    /// Adding replicas never decreases the load of a node, so the number of replicas is found by
+    /// searching from `base_replicas`. The loads are computed from a copy of the positions of the
+    /// ring, so the ring is only modified once. The node receives at least one replica and at most
+    /// `max_replicas`, so a target that cannot be reached with `max_replicas` gives the node
+    /// `max_replicas`. If no other node has a position on the ring, the node receives every point
+    /// and is inserted with `base_replicas`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target_fraction` is not strictly between 0 and 1 or if `max_replicas` is zero.
+    pub fn insert_node_for_load(
+        &mut self,
+        id: &'a T,
+        target_fraction: f64,
+        base_replicas: usize,
+        max_replicas: usize,
+    )
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        assert!(
+            target_fraction > 0.0 && target_fraction < 1.0,
+            "Error: target fraction must be between 0 and 1.",
+        );
+        assert!(max_replicas > 0, "Error: maximum number of replicas must be positive.");
+        let base_replicas = cmp::min(cmp::max(base_replicas, 1), max_replicas);
+        let others: Vec<u64> = self
+            .nodes
+            .iter()
+            .filter(|(_, ids)| ids.iter().any(|other| *other != id))
+            .map(|(hash, _)| *hash)
+            .collect();
+        if others.is_empty() {
+            self.update_replicas(id, base_replicas);
+            return;
+        }
+
+        // `update_replicas` keeps the lowest existing replica indices and then adds the lowest
+        // missing ones, so the positions of the node are generated in the same order.
+        let existing = self.replicas.get(id).cloned().unwrap_or_default();
+        let mut indices = existing
+            .iter()
+            .cloned()
+            .chain((0..).filter(|i| existing.binary_search(i).is_err()));
+        let mut hashes = Vec::new();
+        let mut get_load = |replicas: usize| {
+            while hashes.len() < replicas {
+                let index = indices.next().expect("Expected unbounded replica indices.");
+                hashes.push(self.get_replica_hash(id, index));
+            }
+            gen_load(&others, &hashes[..replicas])
+        };
+
+        // `low` is zero or has a load below the target and `high` has a load of at least the
+        // target, unless it is `max_replicas`.
+        let (mut low, mut high) = (0, base_replicas);
+        let mut low_load = 0.0;
+        let mut high_load = get_load(high);
+        while high_load < target_fraction && high < max_replicas {
+            low = high;
+            low_load = high_load;
+            high = cmp::min(high.saturating_mul(2), max_replicas);
+            high_load = get_load(high);
+        }
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            let load = get_load(mid);
+            if load < target_fraction {
+                low = mid;
+                low_load = load;
+            } else {
+                high = mid;
+                high_load = load;
+            }
+        }
+        if low > 0 && target_fraction - low_load < high_load - target_fraction {
+            self.update_replicas(id, low);
+        } else {
+            self.update_replicas(id, high);
+        }
+    }
+
+    /// Inserts every node of `other` into the ring.
+    ///
+    /// Nodes that are only in `other` are inserted with the same number of replicas. If a node is
+    /// in both rings, it keeps the larger of its two replica counts. Its existing replicas are kept
+    /// and only the missing replicas are added, so the merge moves as few points as possible.
+    pub fn merge<G>(&mut self, other: &Ring<'a, T, G>)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        for (id, replicas) in &other.replicas {
+            let replicas = replicas.len();
+            match self.replicas.get(*id) {
+                Some(existing) if existing.len() >= replicas => {}
+                Some(_) => self.update_replicas(*id, replicas),
+                None => self.insert_node(*id, replicas),
+            }
+        }
+    }
+
+    /// Returns the number of points in `sample` that would be reassigned to a node if it was
+    /// inserted with a number of replicas. The ring itself is not modified.
+    ///
+    /// Only the positions of the inserted replicas are computed, and a point is reassigned if one
+    /// of them is closer to the point than the position that currently owns it. The cost is
+    /// proportional to the number of points rather than the size of the ring.
+    pub fn preview_insert<U>(&self, id: &'a T, replicas: usize, sample: &[U]) -> usize
+        where
+            T: Hash + Eq,
+            U: Hash,
+            H: BuildHasher,
+    {
+        let positions: BTreeSet<u64> =
+            (0..replicas).map(|i| self.get_replica_hash(id, i)).collect();
+        let first = match positions.iter().next() {
+            Some(position) => *position,
+            None => return 0,
+        };
+
+        sample
+            .iter()
+            .filter(|point| {
+                let hash = util::gen_hash(&self.hash_builder, *point);
+                let candidate = positions.range(hash..).next().unwrap_or(&first);
+                let current = self
+                    .nodes
+                    .range(hash..)
+                    .next()
+                    .or_else(|| self.nodes.iter().next());
+                match current {
+                    Some((position, ids)) => {
+                        ids[0] != id && candidate.wrapping_sub(hash) < position.wrapping_sub(hash)
+                    }
+                    None => true,
+                }
+            })
+            .count()
     }
 
     /// Removes a node and all its replicas from the ring.
@@ -77,74 +432,1958 @@ impl<'a, T, H> Ring<'a, T, H> {
             T: Hash + Eq,
             H: BuildHasher,
     {
-        for i in 0..self.replicas[id] {
-            let hash = util::combine_hash(
-                &self.hash_builder,
-                util::gen_hash(&self.hash_builder, id),
-                util::gen_hash(&self.hash_builder, &i),
-            );
-            let should_remove = {
-                if let Some(existing_id) = self.nodes.get(&hash) {
-                    *existing_id == id
-                } else {
-                    false
+        if let Some(replicas) = self.replicas.remove(id) {
+            for i in replicas {
+                self.remove_slot(id, i);
+            }
+            self.release_node(id);
+        }
+    }
+
+    /// Removes every node from the ring. The hash builder of the ring is kept.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.replicas.clear();
+        self.bounded = None;
+    }
+
+    /// Removes a single replica of a node from the ring, leaving the other replicas of the node in
+    /// place. If the last replica of a node is removed, the node is removed from the ring.
+    ///
+    /// Removing replicas from an overloaded node sheds the load of the removed replicas only,
+    /// whereas reinserting the node with fewer replicas would remove its replicas with the
+    /// highest indices.
+    pub fn remove_replica(&mut self, id: &T, replica_index: usize)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        let is_empty = match self.replicas.get_mut(id) {
+            Some(replicas) => match replicas.iter().position(|i| *i == replica_index) {
+                Some(position) => {
+                    replicas.remove(position);
+                    replicas.is_empty()
                 }
-            };
+                None => return,
+            },
+            None => return,
+        };
+        self.remove_slot(id, replica_index);
+        if is_empty {
+            self.replicas.remove(id);
+            self.release_node(id);
+        }
+    }
+
+    // Releases every point assigned to a node by `get_node_bounded`.
+    fn release_node(&mut self, id: &T)
+        where
+            T: Hash + Eq,
+    {
+        if let Some(bounded) = &mut self.bounded {
+            if bounded.loads.remove(id).is_some() {
+                bounded.assignments.retain(|_, assigned_id| *assigned_id != id);
+            }
+        }
+    }
 
-            if should_remove {
-                self.nodes.remove(&hash);
+    fn remove_slot(&mut self, id: &T, replica_index: usize)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        let hash = self.get_replica_hash(id, replica_index);
+        let is_empty = match self.nodes.get_mut(&hash) {
+            Some(ids) => {
+                if let Some(position) = ids.iter().position(|existing_id| *existing_id == id) {
+                    ids.remove(position);
+                }
+                ids.is_empty()
             }
+            None => false,
+        };
+
+        if is_empty {
+            self.nodes.remove(&hash);
         }
-        self.replicas.remove(id);
     }
 
     /// Returns the node associated with a point.
-    pub fn get_node<U>(&self, point: &U) -> &T
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring is empty. See `try_get_node` for a non-panicking variant.
+    pub fn get_node<U>(&self, point: &U) -> &'a T
         where
             U: Hash,
             H: BuildHasher,
     {
-        let hash = util::gen_hash(&self.hash_builder, point);
+        self.try_get_node(point).expect("Error: empty ring.")
+    }
+
+    /// Returns the node associated with a point, or `HashRingError::EmptyRing` if the ring is
+    /// empty.
+    pub fn try_get_node<U>(&self, point: &U) -> Result<&'a T, HashRingError>
+        where
+            U: Hash,
+            H: BuildHasher,
+    {
+        self.get_next_node(util::hash_point(&self.hash_builder, point).value())
+            .ok_or(HashRingError::EmptyRing)
+    }
+
+    /// Returns the keys in `keys` that are mapped to a node, in the same order. No keys are
+    /// returned if the node is not in the ring.
+    pub fn keys_for_node<'k, U>(&self, id: &T, keys: &'k [U]) -> Vec<&'k U>
+        where
+            T: Eq,
+            U: Hash,
+            H: BuildHasher,
+    {
+        keys.iter()
+            .filter(|key| self.try_get_node(*key).is_ok_and(|node| node == id))
+            .collect()
+    }
+
+    /// Returns the nodes associated with a slice of points. The result is the same as calling
+    /// `get_node` for every point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring is empty and `points` is not empty.
+    pub fn get_node_batch<U>(&self, points: &[U]) -> Vec<&'a T>
+        where
+            U: Hash,
+            H: BuildHasher,
+    {
+        points
+            .iter()
+            .map(|point| {
+                self.get_next_node(util::gen_hash(&self.hash_builder, point))
+                    .expect("Error: empty ring.")
+            })
+            .collect()
+    }
+
+    /// Returns the node associated with a point and the position of the ring that the point was
+    /// mapped to, or `None` if the ring is empty.
+    pub fn get_node_with_position<U>(&self, point: &U) -> Option<(&'a T, u64)>
+        where
+            U: Hash,
+            H: BuildHasher,
+    {
+        self.get_next_position(util::gen_hash(&self.hash_builder, point))
+    }
+
+    /// Returns the node associated with a point that was hashed by `util::hash_point` with the
+    /// same hash builder as the ring.
+    pub fn get_node_prehashed(&self, point: &HashedPoint) -> &'a T {
+        match self.get_next_node(point.value()) {
+            Some(node) => node,
+            None => panic!("Error: empty ring."),
+        }
+    }
+
+    /// Returns the node associated with a point combined with a salt, such as a tenant id.
+    ///
+    /// The same point with different salts is mapped to nodes independently. See
+    /// `util::hash_point_salted`.
+    pub fn get_node_salted<U, S>(&self, point: &U, salt: &S) -> &'a T
+        where
+            U: Hash,
+            S: Hash,
+            H: BuildHasher,
+    {
+        self.get_node_prehashed(&util::hash_point_salted(&self.hash_builder, point, salt))
+    }
+
+    /// Returns the node associated with a point given as a sequence of bytes.
+    ///
+    /// The bytes are hashed as they are produced, so large points do not need to be collected in
+    /// memory first. The bytes are written to the hasher without the length prefix that the `Hash`
+    /// implementations of `[u8]` and `Vec<u8>` add, so a point generally maps to a different node
+    /// than `get_node` with the same bytes collected in a slice or vector. A set of keys should be
+    /// looked up with only one of the two methods.
+    pub fn get_node_streaming<I>(&self, bytes: I) -> &'a T
+        where
+            I: IntoIterator<Item = u8>,
+            H: BuildHasher,
+    {
+        let hash = util::gen_hash_streaming(&self.hash_builder, bytes);
         match self.get_next_node(hash) {
-            Some(node) => &*node,
+            Some(node) => node,
             None => panic!("Error: empty ring."),
         }
     }
 
-    fn contains_node(&self, index: u64) -> bool {
-        self.nodes.contains_key(&index)
+    /// Assigns a point to a node using consistent hashing with bounded loads, and returns the node.
+    ///
+    /// Every node can be assigned at most `ceil(load_factor * points / nodes)` points, where
+    /// `points` is the number of assigned points including this one and `nodes` is the number of
+    /// nodes with at least one replica. The point is assigned to the
+    /// first node with spare capacity found by walking the ring clockwise from the point, so a node
+    /// never exceeds the bound regardless of how the points are distributed. A `load_factor`
+    /// close to one gives a tight bound, but moves more points when nodes are inserted or removed.
+    ///
+    /// A point remains assigned to its node until it is released with `release` or its node is
+    /// removed, and assigning an assigned point again returns the same node. Points are identified
+    /// by their hashes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no node has a replica or if `load_factor` is less than one.
+    pub fn get_node_bounded<U>(&mut self, point: &U, load_factor: f64) -> &'a T
+        where
+            T: Hash + Eq,
+            U: Hash,
+            H: BuildHasher,
+    {
+        assert!(load_factor >= 1.0);
+        assert!(!self.nodes.is_empty(), "Error: empty ring.");
+        let hash = util::gen_hash(&self.hash_builder, point);
+        let mut bounded = self.bounded.take().unwrap_or_else(|| {
+            Box::new(BoundedLoads {
+                loads: HashMap::default(),
+                assignments: HashMap::default(),
+            })
+        });
+
+        let id = match bounded.assignments.get(&hash) {
+            Some(id) => *id,
+            None => {
+                let points = bounded.assignments.len() + 1;
+                let nodes = self.replicas.values().filter(|replicas| !replicas.is_empty()).count();
+                let capacity = ceil(load_factor * points as f64 / nodes as f64);
+                let id = self
+                    .iter_from(hash)
+                    .find(|id| bounded.load_of(id) < capacity)
+                    .expect("Error: empty ring.");
+                *bounded.loads.entry(id).or_insert(0) += 1;
+                bounded.assignments.insert(hash, id);
+                id
+            }
+        };
+        self.bounded = Some(bounded);
+        id
+    }
+
+    /// Releases a point that was assigned to a node by `get_node_bounded`, and returns the node.
+    /// Returns `None` if the point is not assigned.
+    pub fn release<U>(&mut self, point: &U) -> Option<&'a T>
+        where
+            T: Hash + Eq,
+            U: Hash,
+            H: BuildHasher,
+    {
+        let hash = util::gen_hash(&self.hash_builder, point);
+        let bounded = self.bounded.as_mut()?;
+        let id = bounded.assignments.remove(&hash)?;
+        if let Some(load) = bounded.loads.get_mut(id) {
+            *load -= 1;
+            if *load == 0 {
+                bounded.loads.remove(id);
+            }
+        }
+        Some(id)
+    }
+
+    /// Returns the number of points assigned to a node by `get_node_bounded`.
+    pub fn load_of(&self, id: &T) -> u64
+        where
+            T: Hash + Eq,
+    {
+        self.bounded.as_ref().map_or(0, |bounded| bounded.load_of(id))
+    }
+
+    /// Returns up to `n` distinct nodes associated with a point, in the order they are found by
+    /// walking the ring clockwise from the point. The first node is the node returned by
+    /// `get_node`.
+    ///
+    /// Fewer than `n` nodes are returned if the ring has fewer than `n` nodes, and no nodes are
+    /// returned if the ring is empty.
+    pub fn get_nodes<U>(&self, point: &U, n: usize) -> Vec<&'a T>
+        where
+            T: Eq,
+            U: Hash,
+            H: BuildHasher,
+    {
+        let hash = util::gen_hash(&self.hash_builder, point);
+        let mut nodes = Vec::with_capacity(n);
+        for id in self.iter_from(hash) {
+            if nodes.len() == n {
+                break;
+            }
+            if !nodes.contains(&id) {
+                nodes.push(id);
+            }
+        }
+        nodes
+    }
+
+    /// Returns the node associated with a point, considering only the nodes in `allowed`.
+    ///
+    /// The ring is walked clockwise from the point and the first node in `allowed` is returned.
+    /// This allows a subset of the nodes to serve a set of points without building a separate
+    /// ring. Returns `None` if no node in the ring is in `allowed`.
+    pub fn get_node_in<U>(&self, point: &U, allowed: &HashSet<&T>) -> Option<&'a T>
+        where
+            T: Hash + Eq,
+            U: Hash,
+            H: BuildHasher,
+    {
+        let hash = util::gen_hash(&self.hash_builder, point);
+        self.iter_from(hash).find(|id| allowed.contains(id))
+    }
+
+    // Returns the nodes of every replica, walking the ring clockwise from a position once.
+    fn iter_from(&self, hash: u64) -> impl Iterator<Item = &'a T> + '_ {
+        self.nodes
+            .range(hash..)
+            .chain(self.nodes.range(..hash))
+            .flat_map(|entry| entry.1.iter().cloned())
     }
 
-    fn get_replica_count(&self, id: &T) -> usize
+    /// Returns `true` if the node is in the ring.
+    pub fn contains_node(&self, id: &T) -> bool
         where
             T: Hash + Eq,
     {
-        self.replicas[id]
+        self.replicas.contains_key(id)
     }
 
-    /// Returns the number of nodes in the ring.
-    pub fn len(&self) -> usize
+    /// Returns the sorted positions of the replicas of a node, or an empty vector if the node is
+    /// not in the ring. Replicas that collide with other replicas are included, so there is one
+    /// position for every replica.
+    pub fn node_positions(&self, id: &T) -> Vec<u64>
         where
             T: Hash + Eq,
+            H: BuildHasher,
     {
-        self.replicas.len()
+        let mut positions: Vec<u64> = self
+            .replicas
+            .get(id)
+            .map(|indices| indices.iter().map(|i| self.get_replica_hash(id, *i)).collect())
+            .unwrap_or_default();
+        positions.sort_unstable();
+        positions
     }
 
-    /// Returns `true` if the ring is empty.
-    pub fn is_empty(&self) -> bool
+    /// Returns the number of replicas of a node, or `None` if the node is not in the ring.
+    pub fn replicas_of(&self, id: &T) -> Option<usize>
         where
             T: Hash + Eq,
     {
-        self.replicas.is_empty()
+        self.replicas.get(id).map(|replicas| replicas.len())
     }
-}
 
-impl<'a, T, H> Default for Ring<'a, T, H>
-    where
-        T: Hash + Eq,
-        H: BuildHasher + Default,
-{
-    fn default() -> Self {
-        Self::with_hasher(Default::default())
+    /// Returns an iterator over the nodes of the ring and their numbers of replicas, in arbitrary
+    /// order.
+    pub fn nodes(&self) -> impl Iterator<Item = (&'a T, usize)> + '_ {
+        self.replicas.iter().map(|(id, replicas)| (*id, replicas.len()))
+    }
+
+    /// Returns the positions of the ring and the nodes that own them, ordered by position.
+    /// A position that is claimed by several replicas appears once for every replica, with the
+    /// owner of the position first.
+    ///
+    /// Together with `replicas_of`, the slots describe the ring completely, so they can be used to
+    /// snapshot the ring or to compare two rings.
+    pub fn slots(&self) -> Vec<(u64, &'a T)> {
+        self.iter_positions().collect()
+    }
+
+    /// Returns an iterator over the positions of the ring and the nodes that own them, in
+    /// ascending order of position. Like `slots`, a position that is claimed by several replicas
+    /// is yielded once for every replica, with the owner of the position first, but the positions
+    /// are not collected.
+    pub fn iter_positions(&self) -> impl Iterator<Item = (u64, &'a T)> + '_ {
+        self.nodes
+            .iter()
+            .flat_map(|(hash, ids)| ids.iter().map(move |id| (*hash, *id)))
+    }
+
+    /// Returns the expected fraction of points that are mapped to each node.
+    ///
+    /// A position receives the points whose hashes fall on the arc between the previous position
+    /// and itself, so the load of a node is the total length of the arcs of the positions it owns,
+    /// divided by the size of the hash space. Unlike the share of replicas, this accounts for the
+    /// uneven gaps between the positions. The loads sum to 1 if the ring is not empty.
+    pub fn estimated_load(&self) -> HashMap<&'a T, f64>
+        where
+            T: Hash + Eq,
+    {
+        let mut loads: HashMap<&'a T, f64> = self.replicas.keys().map(|id| (*id, 0.0)).collect();
+        let last = match self.nodes.keys().next_back() {
+            Some(last) => *last,
+            None => return loads,
+        };
+        let space = u64::MAX as f64 + 1.0;
+        if self.nodes.len() == 1 {
+            loads.insert(self.nodes[&last][0], 1.0);
+            return loads;
+        }
+
+        let mut prev = last;
+        for (hash, ids) in &self.nodes {
+            *loads.entry(ids[0]).or_insert(0.0) += hash.wrapping_sub(prev) as f64 / space;
+            prev = *hash;
+        }
+        loads
+    }
+
+    /// Returns a fingerprint of the nodes of the ring and their numbers of replicas.
+    ///
+    /// Rings with the same nodes and replica counts have the same fingerprint, regardless of the
+    /// order the nodes were inserted in or the hash builder of the ring, so fingerprints can be
+    /// compared to detect rings that have diverged.
+    pub fn fingerprint(&self) -> u64
+        where
+            T: Hash,
+    {
+        util::gen_fingerprint(&self.replicas)
+    }
+
+    /// Returns the ring positions that are claimed by more than one node or replica.
+    ///
+    /// Only the first replica of a position receives points, so a large number of collisions
+    /// degrades the balance of the ring.
+    pub fn collisions(&self) -> Vec<(u64, Vec<&T>)> {
+        self.nodes
+            .iter()
+            .filter(|(_, ids)| ids.len() > 1)
+            .map(|(hash, ids)| (*hash, ids.clone()))
+            .collect()
+    }
+
+    /// Converts the ring into a `NonEmptyRing<T>`, or returns the ring if it does not have any
+    /// positions, which is the case if it is empty or if all its nodes have zero replicas.
+    pub fn into_non_empty(self) -> Result<NonEmptyRing<'a, T, H>, Self> {
+        if self.nodes.is_empty() {
+            Err(self)
+        } else {
+            Ok(NonEmptyRing { ring: self })
+        }
+    }
+
+    /// Consumes the ring and compiles it into an immutable `FrozenRing<T>` that is optimized for
+    /// lookups.
+    pub fn freeze(self) -> FrozenRing<'a, T, H>
+        where
+            T: Hash + Eq,
+    {
+        let ids: Vec<&'a T> = self.replicas.keys().cloned().collect();
+        let indices: HashMap<&'a T, u32> = ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (*id, index as u32))
+            .collect();
+        let slots = self
+            .nodes
+            .iter()
+            .map(|(hash, ids)| (*hash, indices[ids[0]]))
+            .collect();
+        FrozenRing {
+            slots,
+            ids: ids.into_boxed_slice(),
+            hash_builder: self.hash_builder,
+        }
+    }
+
+    /// Returns a `Snapshot<T, H>` of the ring that owns copies of its nodes.
+    ///
+    /// The snapshot contains the positions of the ring, so a ring restored from it with
+    /// `from_snapshot` maps every point to the same node as this ring without hashing the nodes
+    /// again. Points assigned by `get_node_bounded` are not included.
+    pub fn snapshot(&self) -> Snapshot<T, H>
+        where
+            T: Hash + Eq + Clone,
+            H: Clone,
+    {
+        let ids: Vec<&'a T> = self.replicas.keys().cloned().collect();
+        let indices: HashMap<&'a T, u32> = ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (*id, index as u32))
+            .collect();
+        let indices = &indices;
+        Snapshot {
+            nodes: ids
+                .iter()
+                .map(|id| ((*id).clone(), self.replicas[id].clone()))
+                .collect(),
+            slots: self
+                .nodes
+                .iter()
+                .flat_map(|(hash, ids)| ids.iter().map(move |id| (*hash, indices[id])))
+                .collect(),
+            hash_builder: self.hash_builder.clone(),
+        }
+    }
+
+    /// Constructs a new `Ring<T>` from a snapshot that borrows the nodes of the snapshot.
+    pub fn from_snapshot(snapshot: &'a Snapshot<T, H>) -> Self
+        where
+            T: Hash + Eq,
+            H: Clone,
+    {
+        let mut nodes: BTreeMap<u64, Vec<&'a T>> = BTreeMap::new();
+        for (hash, index) in &snapshot.slots {
+            nodes
+                .entry(*hash)
+                .or_default()
+                .push(&snapshot.nodes[*index as usize].0);
+        }
+        Ring {
+            nodes,
+            replicas: snapshot
+                .nodes
+                .iter()
+                .map(|(id, replicas)| (id, replicas.clone()))
+                .collect(),
+            bounded: None,
+            hash_builder: snapshot.hash_builder.clone(),
+        }
+    }
+
+    /// Returns an estimate of the number of bytes used by the ring: every position on the ring with
+    /// its nodes, the replica positions of every node and, once `get_node_bounded` has been called,
+    /// the cached loads and assignments of the bounded lookup.
+    pub fn memory_footprint(&self) -> usize {
+        mem::size_of::<Self>()
+            + self
+                .nodes
+                .values()
+                .map(gen_slot_footprint::<u64, T>)
+                .sum::<usize>()
+            + self
+                .replicas
+                .values()
+                .map(|replicas| {
+                    mem::size_of::<(&T, Vec<usize>)>()
+                        + replicas.capacity() * mem::size_of::<usize>()
+                })
+                .sum::<usize>()
+            + self.bounded.as_ref().map_or(0, |bounded| {
+                mem::size_of::<BoundedLoads<T>>()
+                    + bounded.loads.len() * mem::size_of::<(&T, u64)>()
+                    + bounded.assignments.len() * mem::size_of::<(u64, &T)>()
+            })
+    }
+
+    /// Returns the number of nodes in the ring.
+    pub fn len(&self) -> usize
+        where
+            T: Hash + Eq,
+    {
+        self.replicas.len()
+    }
+
+    /// Returns `true` if the ring is empty.
+    pub fn is_empty(&self) -> bool
+        where
+            T: Hash + Eq,
+    {
+        self.replicas.is_empty()
+    }
+}
+
+/// A hashing ring that is guaranteed to contain at least one node.
+///
+/// A `NonEmptyRing<T>` is produced by `Ring::into_non_empty` and, unlike `Ring<T>`, finding the
+/// node associated with a point cannot fail.
+pub struct NonEmptyRing<'a, T, H = util::DefaultState> {
+    ring: Ring<'a, T, H>,
+}
+
+impl<'a, T, H> NonEmptyRing<'a, T, H> {
+    /// Inserts a node into the ring with a number of replicas. If the node is already in the ring,
+    /// its replicas are replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `replicas` is zero and the node owns every position of the ring, since the ring
+    /// would not have any positions left.
+    pub fn insert_node(&mut self, id: &'a T, replicas: usize)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        assert!(
+            replicas > 0 || self.ring.nodes.values().flatten().any(|other| *other != id),
+            "Error: cannot remove the last position.",
+        );
+        self.ring.insert_node(id, replicas);
+    }
+
+    /// Removes a node and all its replicas from the ring. If the ring does not have any positions
+    /// left, the `Ring<T>` is returned instead.
+    pub fn remove_node(mut self, id: &T) -> Result<Self, Ring<'a, T, H>>
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        self.ring.remove_node(id);
+        self.ring.into_non_empty()
+    }
+
+    /// Returns the node associated with a point.
+    pub fn get_node<U>(&self, point: &U) -> &'a T
+        where
+            U: Hash,
+            H: BuildHasher,
+    {
+        self.ring.get_node(point)
+    }
+
+    /// Returns the number of nodes in the ring.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize
+        where
+            T: Hash + Eq,
+    {
+        self.ring.len()
+    }
+
+    /// Converts the ring back into a `Ring<T>`.
+    pub fn into_inner(self) -> Ring<'a, T, H> {
+        self.ring
+    }
+}
+
+/// An immutable hashing ring produced by `Ring::freeze`.
+///
+/// The ring positions are stored in a sorted slice and a point is mapped to a node using a binary
+/// search, which has better cache behavior than walking the `BTreeMap` of a live ring. A frozen ring
+/// maps every point to the same node as the ring it was produced from.
+pub struct FrozenRing<'a, T, H = util::DefaultState> {
+    slots: Box<[(u64, u32)]>,
+    ids: Box<[&'a T]>,
+    hash_builder: H,
+}
+
+impl<'a, T, H> FrozenRing<'a, T, H> {
+    /// Returns the node associated with a point.
+    pub fn get_node<U>(&self, point: &U) -> &'a T
+        where
+            U: Hash,
+            H: BuildHasher,
+    {
+        if self.slots.is_empty() {
+            panic!("Error: empty ring.");
+        }
+        let hash = util::gen_hash(&self.hash_builder, point);
+        let index = match self.slots.binary_search_by_key(&hash, |slot| slot.0) {
+            Ok(index) => index,
+            Err(index) => index % self.slots.len(),
+        };
+        self.ids[self.slots[index].1 as usize]
+    }
+}
+
+/// An owned snapshot of a `Ring<T>` produced by `Ring::snapshot`.
+///
+/// With the `serde` feature, a snapshot can be serialized to persist a ring and deserialized to
+/// restore it with `Ring::from_snapshot`. The hash builder is serialized as well, so it must be
+/// seeded deterministically, such as `util::SipState`, for the restored ring to hash points to
+/// the same positions. `RandomState` cannot be serialized, and its seeds differ between
+/// processes.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Snapshot<T, H> {
+    nodes: Vec<(T, Vec<usize>)>,
+    slots: Vec<(u64, u32)>,
+    hash_builder: H,
+}
+
+impl<'a, T, H> Default for Ring<'a, T, H>
+    where
+        T: Hash + Eq,
+        H: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(Default::default())
+    }
+}
+
+impl<'a, T, U, H> HashRing<'a, T, U> for Ring<'a, T, H>
+    where
+        T: Hash + Eq,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn try_get_node(&self, point: &U) -> Result<&'a T, HashRingError> {
+        self.try_get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.replicas.keys().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<'a, T, U, H> DynamicHashRing<'a, T, U> for Ring<'a, T, H>
+    where
+        T: Hash + Eq,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn remove_node(&mut self, id: &T) {
+        self.remove_node(id)
+    }
+}
+
+impl<'a, T, U, H> CowHashRing<T, U> for Ring<'a, T, H>
+    where
+        T: Hash + Eq + Clone,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn get_node_cow(&self, point: &U) -> Cow<'_, T> {
+        Cow::Borrowed(self.get_node(point))
+    }
+}
+
+impl<'a, T, H> Router for Ring<'a, T, H>
+    where
+        H: BuildHasher,
+{
+    type Output = &'a T;
+
+    fn route<K>(&self, key: &K) -> &'a T
+        where
+            K: Hash,
+    {
+        self.get_node(key)
+    }
+}
+
+/// A builder for a `Ring` with a hash builder and an initial list of nodes.
+pub struct RingBuilder<'a, T, H = util::DefaultState> {
+    nodes: Vec<(&'a T, usize)>,
+    hash_builder: H,
+}
+
+impl<'a, T> RingBuilder<'a, T, util::DefaultState> {
+    /// Constructs a new `RingBuilder<T>` for an empty ring with the default hash builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<'a, T, H> RingBuilder<'a, T, H> {
+    /// Sets the hash builder of the ring.
+    pub fn hasher<G>(self, hash_builder: G) -> RingBuilder<'a, T, G> {
+        RingBuilder {
+            nodes: self.nodes,
+            hash_builder,
+        }
+    }
+
+    /// Adds a node with a number of replicas to the ring.
+    pub fn node(mut self, id: &'a T, replicas: usize) -> Self {
+        self.nodes.push((id, replicas));
+        self
+    }
+
+    /// Adds several nodes with their numbers of replicas to the ring.
+    pub fn nodes<I>(mut self, nodes: I) -> Self
+        where
+            I: IntoIterator<Item = (&'a T, usize)>,
+    {
+        self.nodes.extend(nodes);
+        self
+    }
+
+    /// Constructs the ring. The nodes are inserted in the order they were added.
+    pub fn build(self) -> Ring<'a, T, H>
+        where
+            T: Hash + Eq,
+            H: BuildHasher + Default,
+    {
+        Ring::from_nodes_with_hasher(self.hash_builder, self.nodes)
+    }
+}
+
+impl<'a, T, H> Default for RingBuilder<'a, T, H>
+    where
+        H: Default,
+{
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            hash_builder: Default::default(),
+        }
+    }
+}
+
+/// A hashing ring implemented using consistent hashing that owns its nodes.
+///
+/// Unlike `Ring`, the ring does not borrow the ids of its nodes, so it can be moved or stored
+/// without the storage of the ids. Each id is stored once and shared by its replicas, and lookups
+/// return references to the ids stored in the ring. A point is mapped to the same node as a `Ring`
+/// with the same nodes and hash builder.
+pub struct OwnedRing<T, H = util::DefaultState> {
+    nodes: BTreeMap<u64, Vec<Arc<T>>>,
+    replicas: HashMap<Arc<T>, usize>,
+    hash_builder: H,
+}
+
+impl<T> OwnedRing<T, util::DefaultState> {
+    /// Constructs a new, empty `OwnedRing<T>`.
+    pub fn new() -> Self
+        where
+            T: Hash + Eq,
+    {
+        Self::default()
+    }
+}
+
+impl<T, H> OwnedRing<T, H> {
+    /// Constructs a new, empty `OwnedRing<T>` with a specified hash builder.
+    pub fn with_hasher(hash_builder: H) -> Self
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        Self {
+            nodes: BTreeMap::new(),
+            replicas: HashMap::default(),
+            hash_builder,
+        }
+    }
+
+    /// Inserts a node into the ring with a number of replicas. If the node is already in the ring,
+    /// its replicas are replaced.
+    pub fn insert_node(&mut self, id: T, replicas: usize)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        self.remove_node(&id);
+        let id = Arc::new(id);
+        for i in 0..replicas {
+            let hash = gen_replica_hash(&self.hash_builder, &*id, i);
+            self.nodes.entry(hash).or_default().push(Arc::clone(&id));
+        }
+        self.replicas.insert(id, replicas);
+    }
+
+    /// Removes a node and all its replicas from the ring.
+    pub fn remove_node(&mut self, id: &T)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        if let Some(replicas) = self.replicas.remove(id) {
+            for i in 0..replicas {
+                let hash = gen_replica_hash(&self.hash_builder, id, i);
+                let is_empty = match self.nodes.get_mut(&hash) {
+                    Some(ids) => {
+                        ids.retain(|existing_id| **existing_id != *id);
+                        ids.is_empty()
+                    }
+                    None => false,
+                };
+                if is_empty {
+                    self.nodes.remove(&hash);
+                }
+            }
+        }
+    }
+
+    /// Returns the node associated with a point.
+    pub fn get_node<U>(&self, point: &U) -> &T
+        where
+            U: Hash,
+            H: BuildHasher,
+    {
+        self.try_get_node(point).expect("Error: empty ring.")
+    }
+
+    /// Returns the node associated with a point, or `HashRingError::EmptyRing` if the ring is
+    /// empty.
+    pub fn try_get_node<U>(&self, point: &U) -> Result<&T, HashRingError>
+        where
+            U: Hash,
+            H: BuildHasher,
+    {
+        self.find(util::gen_hash(&self.hash_builder, point))
+            .map(|id| &**id)
+            .ok_or(HashRingError::EmptyRing)
+    }
+
+    fn find(&self, hash: u64) -> Option<&Arc<T>> {
+        self.nodes
+            .range(hash..)
+            .next()
+            .or_else(|| self.nodes.iter().next())
+            .map(|entry| &entry.1[0])
+    }
+
+    /// Returns the ids of the nodes in the ring and their numbers of replicas, in no particular
+    /// order.
+    pub fn nodes(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.replicas.iter().map(|(id, replicas)| (&**id, *replicas))
+    }
+
+    /// Returns the number of nodes in the ring.
+    pub fn len(&self) -> usize {
+        self.replicas.len()
+    }
+
+    /// Returns `true` if the ring is empty.
+    pub fn is_empty(&self) -> bool {
+        self.replicas.is_empty()
+    }
+}
+
+impl<T, H> Default for OwnedRing<T, H>
+    where
+        T: Hash + Eq,
+        H: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(Default::default())
+    }
+}
+
+impl<T, U, H> CowHashRing<Arc<T>, U> for OwnedRing<T, H>
+    where
+        U: Hash,
+        H: BuildHasher,
+{
+    fn get_node_cow(&self, point: &U) -> Cow<'_, Arc<T>> {
+        let hash = util::gen_hash(&self.hash_builder, point);
+        Cow::Owned(Arc::clone(self.find(hash).expect("Error: empty ring.")))
+    }
+}
+
+/// A hashing ring implemented using consistent hashing with 32-bit positions.
+///
+/// A `Ring32<T>` behaves like a `Ring<T>`, but the positions of the replicas are the hashes of the
+/// replicas truncated to 32 bits, which reduces the memory used by each position. The trade-off is
+/// a much higher probability of collisions: with `n` replicas in total, the expected number of
+/// colliding pairs is approximately `n^2 / 2^33`, which is about one for 100,000 replicas. As in
+/// a `Ring<T>`, colliding replicas are all kept, and the position is owned by the replica that was
+/// inserted first until it is removed, so the node of a shadowed replica receives slightly fewer
+/// points.
+pub struct Ring32<'a, T, H = util::DefaultState> {
+    nodes: BTreeMap<u32, Vec<&'a T>>,
+    replicas: HashMap<&'a T, usize>,
+    hash_builder: H,
+}
+
+impl<'a, T> Ring32<'a, T, util::DefaultState> {
+    /// Constructs a new, empty `Ring32<T>`.
+    pub fn new() -> Self
+        where
+            T: Hash + Eq,
+    {
+        Self::default()
+    }
+}
+
+impl<'a, T, H> Ring32<'a, T, H> {
+    /// Constructs a new, empty `Ring32<T>` with a specified hash builder.
+    pub fn with_hasher(hash_builder: H) -> Self
+        where
+            T: Hash + Eq,
+            H: BuildHasher + Default,
+    {
+        Self {
+            nodes: BTreeMap::new(),
+            replicas: HashMap::default(),
+            hash_builder,
+        }
+    }
+
+    fn get_replica_hash(&self, id: &T, index: usize) -> u32
+        where
+            T: Hash,
+            H: BuildHasher,
+    {
+        gen_replica_hash(&self.hash_builder, id, index) as u32
+    }
+
+    /// Inserts a node into the ring with a number of replicas. If the node is already in the ring,
+    /// its replicas are replaced.
+    pub fn insert_node(&mut self, id: &'a T, replicas: usize)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        self.remove_node(id);
+        for i in 0..replicas {
+            let hash = self.get_replica_hash(id, i);
+            self.nodes.entry(hash).or_default().push(id);
+        }
+        self.replicas.insert(id, replicas);
+    }
+
+    /// Removes a node and all its replicas from the ring.
+    pub fn remove_node(&mut self, id: &T)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        if let Some(replicas) = self.replicas.remove(id) {
+            for i in 0..replicas {
+                let hash = self.get_replica_hash(id, i);
+                let is_empty = match self.nodes.get_mut(&hash) {
+                    Some(ids) => {
+                        if let Some(position) = ids.iter().position(|other| *other == id) {
+                            ids.remove(position);
+                        }
+                        ids.is_empty()
+                    }
+                    None => false,
+                };
+                if is_empty {
+                    self.nodes.remove(&hash);
+                }
+            }
+        }
+    }
+
+    /// Returns the node associated with a point.
+    pub fn get_node<U>(&self, point: &U) -> &'a T
+        where
+            U: Hash,
+            H: BuildHasher,
+    {
+        self.try_get_node(point).expect("Error: empty ring.")
+    }
+
+    /// Returns the node associated with a point, or `HashRingError::EmptyRing` if the ring has no
+    /// replicas.
+    pub fn try_get_node<U>(&self, point: &U) -> Result<&'a T, HashRingError>
+        where
+            U: Hash,
+            H: BuildHasher,
+    {
+        let hash = util::gen_hash(&self.hash_builder, point) as u32;
+        self.nodes
+            .range(hash..)
+            .next()
+            .or_else(|| self.nodes.iter().next())
+            .map(|entry| entry.1[0])
+            .ok_or(HashRingError::EmptyRing)
+    }
+
+    /// Returns an estimate of the number of bytes used by the ring: every 32-bit position with its
+    /// nodes and the replica count of every node. Since nodes only store a count instead of their
+    /// positions, this is usually smaller than the footprint of a `Ring` with the same nodes.
+    pub fn memory_footprint(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.nodes.values().map(gen_slot_footprint::<u32, T>).sum::<usize>()
+            + self.replicas.len() * mem::size_of::<(&T, usize)>()
+    }
+
+    /// Returns the number of nodes in the ring.
+    pub fn len(&self) -> usize
+        where
+            T: Hash + Eq,
+    {
+        self.replicas.len()
+    }
+
+    /// Returns `true` if the ring is empty.
+    pub fn is_empty(&self) -> bool
+        where
+            T: Hash + Eq,
+    {
+        self.replicas.is_empty()
+    }
+}
+
+impl<'a, T, H> Default for Ring32<'a, T, H>
+    where
+        T: Hash + Eq,
+        H: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(Default::default())
+    }
+}
+
+impl<'a, T, U, H> HashRing<'a, T, U> for Ring32<'a, T, H>
+    where
+        T: Hash + Eq,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn try_get_node(&self, point: &U) -> Result<&'a T, HashRingError> {
+        self.try_get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.replicas.keys().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+#[test]
+fn test_get_node_bounded_zero_replicas() {
+    let nodes: Vec<u64> = (0..4).collect();
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 0);
+    ring.insert_node(&nodes[1], 0);
+    ring.insert_node(&nodes[2], 10);
+    ring.insert_node(&nodes[3], 10);
+
+    for point in 0..100 {
+        ring.get_node_bounded(&point, 1.0);
+    }
+    assert_eq!(ring.load_of(&nodes[2]), 50);
+    assert_eq!(ring.load_of(&nodes[3]), 50);
+}
+
+#[test]
+#[should_panic]
+fn test_get_node_bounded_no_replicas() {
+    let node = 0;
+    let mut ring = Ring::new();
+    ring.insert_node(&node, 0);
+    ring.get_node_bounded(&0, 1.25);
+}
+
+#[test]
+fn test_reinsert_node() {
+    let nodes = [0, 1];
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 3);
+    ring.insert_node(&nodes[1], 3);
+    ring.insert_node(&nodes[0], 3);
+    assert_eq!(ring.slots().len(), 6);
+
+    ring.remove_node(&nodes[0]);
+    assert_eq!(ring.len(), 1);
+    assert_eq!(ring.slots().len(), 3);
+    for point in 0..1000 {
+        assert_eq!(ring.get_node(&point), &nodes[1]);
+    }
+
+    let ring = Ring::from_nodes(vec![(&nodes[0], 3), (&nodes[0], 2)]);
+    assert_eq!(ring.slots().len(), 2);
+}
+
+#[test]
+fn test_collisions() {
+    let mut ring = Ring::with_hasher(util::ConstantState);
+    ring.insert_node(&0, 1);
+    ring.insert_node(&1, 1);
+
+    let mut collisions = ring.collisions();
+    assert_eq!(collisions.len(), 1);
+    collisions[0].1.sort();
+    assert_eq!(collisions[0].1, vec![&0, &1]);
+}
+
+#[test]
+fn test_no_collisions() {
+    let mut ring = Ring::new();
+    ring.insert_node(&0, 10);
+    ring.insert_node(&1, 10);
+
+    assert!(ring.collisions().is_empty());
+}
+
+#[test]
+fn test_freeze() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let points: Vec<u64> = (0..1000).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+    let expected: Vec<&u64> = points.iter().map(|point| ring.get_node(point)).collect();
+    let frozen = ring.freeze();
+
+    for (point, node) in points.iter().zip(expected) {
+        assert_eq!(frozen.get_node(point), node);
+    }
+}
+
+#[test]
+fn test_get_node_by() {
+    struct Request {
+        id: u64,
+    }
+
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+
+    for id in 0..100 {
+        let request = Request { id };
+        assert_eq!(
+            ring.get_node_by(&request, |request| request.id),
+            ring.get_node(&id),
+        );
+    }
+}
+
+#[test]
+fn test_insert_node_by_capacity() {
+    const GB: u64 = 1 << 30;
+
+    let nodes: Vec<u64> = (0..4).collect();
+    let mut ring = Ring::new();
+    ring.insert_node_by_capacity(&nodes[0], GB, GB / 4);
+    ring.insert_node_by_capacity(&nodes[1], 2 * GB, GB / 4);
+    ring.insert_node_by_capacity(&nodes[2], 4 * GB, GB / 4);
+    ring.insert_node_by_capacity(&nodes[3], GB / 16, GB / 4);
+
+    assert_eq!(ring.replicas_of(&nodes[0]), Some(4));
+    assert_eq!(ring.replicas_of(&nodes[1]), Some(8));
+    assert_eq!(ring.replicas_of(&nodes[2]), Some(16));
+    assert_eq!(ring.replicas_of(&nodes[3]), Some(1));
+}
+
+#[test]
+fn test_get_node_in() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+    let allowed: HashSet<&u64> = [&nodes[2], &nodes[5], &nodes[7]].iter().cloned().collect();
+    let everything: HashSet<&u64> = nodes.iter().collect();
+
+    for point in 0..1000 {
+        assert!(allowed.contains(ring.get_node_in(&point, &allowed).unwrap()));
+        assert_eq!(ring.get_node_in(&point, &everything), Some(ring.get_node(&point)));
+        assert_eq!(ring.get_node_in(&point, &HashSet::default()), None);
+    }
+}
+
+#[test]
+fn test_merge() {
+    let nodes: Vec<u64> = (0..4).collect();
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 1);
+    ring.insert_node(&nodes[1], 5);
+    ring.insert_node(&nodes[2], 2);
+    let mut other = Ring::new();
+    other.insert_node(&nodes[1], 3);
+    other.insert_node(&nodes[2], 4);
+    other.insert_node(&nodes[3], 6);
+    let positions = ring.node_positions(&nodes[2]);
+
+    ring.merge(&other);
+
+    assert_eq!(ring.len(), 4);
+    assert_eq!(ring.replicas_of(&nodes[0]), Some(1));
+    assert_eq!(ring.replicas_of(&nodes[1]), Some(5));
+    assert_eq!(ring.replicas_of(&nodes[2]), Some(4));
+    assert_eq!(ring.replicas_of(&nodes[3]), Some(6));
+    let merged_positions = ring.node_positions(&nodes[2]);
+    assert!(positions.iter().all(|position| merged_positions.contains(position)));
+}
+
+#[test]
+fn test_memory_footprint() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    let mut replicated_ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 1);
+        replicated_ring.insert_node(node, 10);
+    }
+
+    assert!(ring.memory_footprint() > Ring::<u64>::new().memory_footprint());
+    assert!(replicated_ring.memory_footprint() > ring.memory_footprint());
+}
+
+#[test]
+fn test_into_non_empty() {
+    let nodes: Vec<u64> = (0..2).collect();
+    let ring: Ring<u64> = Ring::new();
+    let mut ring = ring.into_non_empty().err().unwrap();
+    ring.insert_node(&nodes[0], 10);
+    let mut ring = ring.into_non_empty().ok().unwrap();
+    ring.insert_node(&nodes[1], 10);
+
+    assert_eq!(ring.len(), 2);
+    for point in 0..100 {
+        assert!(nodes.contains(ring.get_node(&point)));
+    }
+
+    let ring = ring.remove_node(&nodes[0]).ok().unwrap();
+    assert_eq!(ring.get_node(&0), &nodes[1]);
+    let ring = ring.remove_node(&nodes[1]).err().unwrap();
+    assert!(ring.is_empty());
+}
+
+#[test]
+fn test_into_non_empty_zero_replicas() {
+    let nodes: Vec<u64> = (0..2).collect();
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 0);
+    let mut ring = ring.into_non_empty().err().unwrap();
+    assert_eq!(ring.len(), 1);
+
+    ring.insert_node(&nodes[1], 1);
+    let mut ring = ring.into_non_empty().ok().unwrap();
+    ring.insert_node(&nodes[0], 0);
+    assert_eq!(ring.get_node(&0), &nodes[1]);
+}
+
+#[test]
+#[should_panic]
+fn test_non_empty_remove_last_position() {
+    let node = 0;
+    let mut ring = Ring::new();
+    ring.insert_node(&node, 3);
+    let mut ring = ring.into_non_empty().ok().unwrap();
+    ring.insert_node(&node, 0);
+}
+
+#[test]
+fn test_get_node_streaming() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+    let bytes: Vec<u8> = (0..1000).map(|i| (i % 256) as u8).collect();
+    let chunks = bytes.chunks(100).flat_map(|chunk| chunk.iter().cloned());
+    let mut hasher = ring.hash_builder.build_hasher();
+    core::hash::Hasher::write(&mut hasher, &bytes);
+
+    assert_eq!(
+        ring.get_node_streaming(chunks),
+        ring.get_next_node(core::hash::Hasher::finish(&hasher)).unwrap(),
+    );
+}
+
+#[test]
+fn test_preview_insert() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let keys: Vec<u64> = (0..1000).collect();
+    let mut ring = Ring::new();
+    for node in &nodes[..9] {
+        ring.insert_node(node, 10);
+    }
+    let before: Vec<&u64> = keys.iter().map(|key| ring.get_node(key)).collect();
+
+    let count = ring.preview_insert(&nodes[9], 10, &keys);
+    assert_eq!(ring.len(), 9);
+    assert!(keys.iter().zip(&before).all(|(key, node)| ring.get_node(key) == *node));
+    assert_eq!(ring.preview_insert(&nodes[0], 10, &keys), 0);
+    assert_eq!(ring.preview_insert(&nodes[9], 0, &keys), 0);
+
+    ring.insert_node(&nodes[9], 10);
+    let moved = keys
+        .iter()
+        .zip(&before)
+        .filter(|(key, node)| ring.get_node(*key) != **node)
+        .count();
+    assert!(count > 0);
+    assert_eq!(count, moved);
+    assert_eq!(Ring::<u64>::new().preview_insert(&nodes[0], 1, &keys), keys.len());
+}
+
+#[test]
+fn test_slots() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, *node as usize + 1);
+    }
+    let slots = ring.slots();
+
+    let total_replicas: usize = nodes.iter().map(|node| ring.replicas_of(node).unwrap()).sum();
+    assert_eq!(slots.len(), total_replicas);
+    assert!(slots.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert_eq!(ring.replicas_of(&10), None);
+}
+
+#[test]
+fn test_iter_positions() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, *node as usize + 1);
+    }
+    let positions: Vec<(u64, &u64)> = ring.iter_positions().collect();
+
+    assert_eq!(positions.len(), 55);
+    assert!(positions.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    for (hash, id) in &positions {
+        assert_eq!(ring.get_next_node(*hash), Some(*id));
+    }
+
+    let mut ring = Ring::with_hasher(util::ConstantState);
+    ring.insert_node(&0, 2);
+    ring.insert_node(&1, 3);
+    let positions: Vec<(u64, &u64)> = ring.iter_positions().collect();
+    assert_eq!(positions.len(), 5);
+    assert!(positions.iter().all(|(hash, _)| *hash == 0));
+    assert_eq!(positions[0].1, &0);
+}
+
+#[test]
+fn test_estimated_load() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    assert!(ring.estimated_load().is_empty());
+    ring.insert_node(&nodes[0], 3);
+    assert!((ring.estimated_load()[&nodes[0]] - 1.0).abs() < 1e-9);
+
+    for node in &nodes[1..] {
+        ring.insert_node(node, 100);
+    }
+    let loads = ring.estimated_load();
+    assert_eq!(loads.len(), nodes.len());
+    assert!((loads.values().sum::<f64>() - 1.0).abs() < 1e-9);
+
+    let points = 100_000;
+    let mut counts: HashMap<_, _> = HashMap::default();
+    for point in 0..points {
+        *counts.entry(ring.get_node(&point)).or_insert(0) += 1;
+    }
+    for (id, load) in &loads {
+        let actual = *counts.get(id).unwrap_or(&0) as f64 / points as f64;
+        assert!((actual - load).abs() < 0.01);
+    }
+}
+
+#[test]
+fn test_insert_node_for_load() {
+    let nodes = [0, 1];
+    let mut ring = Ring::new();
+    ring.insert_node_for_load(&nodes[0], 0.25, 100, 10_000);
+    assert_eq!(ring.replicas_of(&nodes[0]), Some(100));
+    ring.insert_node_for_load(&nodes[1], 0.75, 100, 10_000);
+
+    let points = 100_000;
+    let mut counts: HashMap<_, _> = HashMap::default();
+    for point in 0..points {
+        *counts.entry(ring.get_node(&point)).or_insert(0) += 1;
+    }
+    let load = counts[&nodes[0]] as f64 / points as f64;
+    assert!((load - 0.25).abs() < 0.02);
+    let load = counts[&nodes[1]] as f64 / points as f64;
+    assert!((load - 0.75).abs() < 0.02);
+
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[1], 0);
+    ring.insert_node_for_load(&nodes[0], 0.25, 100, 10_000);
+    assert_eq!(ring.replicas_of(&nodes[0]), Some(100));
+
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[1], 1000);
+    ring.insert_node_for_load(&nodes[0], 0.99, 1, 64);
+    assert_eq!(ring.replicas_of(&nodes[0]), Some(64));
+}
+
+#[test]
+fn test_fingerprint() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    let mut other = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+    for node in nodes.iter().rev() {
+        other.insert_node(node, 10);
+    }
+    assert_eq!(ring.fingerprint(), other.fingerprint());
+
+    other.insert_node(&nodes[0], 11);
+    assert_ne!(ring.fingerprint(), other.fingerprint());
+    other.insert_node(&nodes[0], 10);
+    assert_eq!(ring.fingerprint(), other.fingerprint());
+    other.remove_replica(&nodes[0], 1);
+    other.update_replicas(&nodes[0], 10);
+    assert_eq!(ring.fingerprint(), other.fingerprint());
+    other.remove_node(&nodes[1]);
+    assert_ne!(ring.fingerprint(), other.fingerprint());
+}
+
+#[test]
+fn test_get_node_salted() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+
+    let moved = (0..1000u64)
+        .filter(|point| {
+            assert_eq!(ring.get_node_salted(point, &"a"), ring.get_node_salted(point, &"a"));
+            ring.get_node_salted(point, &"a") != ring.get_node_salted(point, &"b")
+        })
+        .count();
+    assert!(moved > 500);
+}
+
+#[test]
+fn test_get_nodes_u64() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let keys: Vec<u64> = (0..1000).collect();
+    let mut ring = Ring::with_hasher(util::IntegerState);
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+
+    let expected: Vec<&u64> = keys.iter().map(|key| ring.get_node(key)).collect();
+    assert_eq!(ring.get_nodes_u64(&keys), expected);
+}
+
+#[test]
+fn test_remove_replica() {
+    let nodes: Vec<u64> = (0..2).collect();
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 3);
+    ring.insert_node(&nodes[1], 1);
+    let removed_hash = ring.get_replica_hash(&nodes[0], 1);
+
+    ring.remove_replica(&nodes[0], 1);
+    assert_eq!(ring.replicas_of(&nodes[0]), Some(2));
+    assert!(!ring.nodes.contains_key(&removed_hash));
+    assert!(ring.nodes.contains_key(&ring.get_replica_hash(&nodes[0], 0)));
+    assert!(ring.nodes.contains_key(&ring.get_replica_hash(&nodes[0], 2)));
+    assert_eq!(ring.slots().len(), 3);
+
+    ring.remove_replica(&nodes[0], 1);
+    assert_eq!(ring.replicas_of(&nodes[0]), Some(2));
+    ring.remove_replica(&nodes[1], 0);
+    assert_eq!(ring.replicas_of(&nodes[1]), None);
+    assert_eq!(ring.len(), 1);
+
+    ring.remove_node(&nodes[0]);
+    assert!(ring.is_empty());
+    assert!(ring.slots().is_empty());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_from_nodes() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let hash_builder = RandomState::new();
+    let mut ring = Ring::with_hasher(hash_builder.clone());
+    for node in &nodes {
+        ring.insert_node(node, *node as usize + 1);
+    }
+    let other = Ring::from_nodes_with_hasher(
+        hash_builder,
+        nodes.iter().map(|node| (node, *node as usize + 1)),
+    );
+
+    assert_eq!(other.len(), nodes.len());
+    assert_eq!(other.slots(), ring.slots());
+    assert_eq!(Ring::from_nodes(vec![(&nodes[0], 3)]).slots().len(), 3);
+}
+
+#[test]
+fn test_ring32() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    let mut ring32 = Ring32::new();
+    for node in &nodes {
+        ring.insert_node(node, 10);
+        ring32.insert_node(node, 10);
+    }
+    ring32.remove_node(&nodes[0]);
+    ring.remove_node(&nodes[0]);
+
+    let slots: Vec<(u32, &u64)> = ring32.nodes.iter().map(|(hash, ids)| (*hash, ids[0])).collect();
+    assert_eq!(slots.len(), 90);
+    for point in 0..1000u64 {
+        let hash = util::gen_hash(&ring32.hash_builder, &point) as u32;
+        let expected = slots
+            .iter()
+            .find(|slot| slot.0 >= hash)
+            .unwrap_or(&slots[0])
+            .1;
+        assert_eq!(ring32.get_node(&point), expected);
+    }
+    assert_eq!(ring32.nodes.len(), ring.nodes.len());
+    let slot_bytes: usize = ring.nodes.values().map(gen_slot_footprint::<u64, u64>).sum();
+    let slot_bytes32: usize = ring32.nodes.values().map(gen_slot_footprint::<u32, u64>).sum();
+    assert!(slot_bytes32 < slot_bytes);
+    assert_eq!(slot_bytes - slot_bytes32, ring.nodes.len() * 4);
+}
+
+#[test]
+fn test_get_nodes() {
+    let nodes: Vec<u64> = (0..3).collect();
+    let mut ring = Ring::new();
+    assert!(ring.get_nodes(&0, 5).is_empty());
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+
+    for point in 0..100 {
+        let mut replicas = ring.get_nodes(&point, 5);
+        assert_eq!(replicas[0], ring.get_node(&point));
+        assert_eq!(ring.get_nodes(&point, 2), &replicas[..2]);
+        replicas.sort();
+        assert_eq!(replicas, vec![&0, &1, &2]);
+    }
+}
+
+#[test]
+fn test_try_get_node() {
+    let nodes = [0, 1];
+    let mut ring = Ring::new();
+    assert_eq!(ring.try_get_node(&0), Err(HashRingError::EmptyRing));
+
+    ring.insert_node(&nodes[0], 1);
+    ring.insert_node(&nodes[1], 1);
+    for point in 0..10 {
+        assert_eq!(ring.try_get_node(&point), Ok(ring.get_node(&point)));
+    }
+
+    ring.remove_node(&nodes[0]);
+    ring.remove_node(&nodes[1]);
+    assert_eq!(ring.try_get_node(&0), Err(HashRingError::EmptyRing));
+}
+
+#[test]
+fn test_colliding_replicas() {
+    let nodes = [0, 1];
+    let mut ring = Ring::with_hasher(util::ConstantState);
+    ring.insert_node(&nodes[0], 3);
+    ring.insert_node(&nodes[1], 3);
+
+    assert_eq!(ring.slots().len(), 6);
+    assert_eq!(ring.replicas_of(&nodes[0]), Some(3));
+    assert_eq!(ring.replicas_of(&nodes[1]), Some(3));
+    assert_eq!(ring.get_node(&0), &nodes[0]);
+
+    ring.remove_replica(&nodes[1], 0);
+    assert_eq!(ring.slots().len(), 5);
+    ring.remove_node(&nodes[0]);
+    assert_eq!(ring.slots(), vec![(0, &nodes[1]), (0, &nodes[1])]);
+    assert_eq!(ring.get_node(&0), &nodes[1]);
+
+    ring.remove_node(&nodes[1]);
+    assert!(ring.slots().is_empty());
+    assert_eq!(ring.try_get_node(&0), Err(HashRingError::EmptyRing));
+}
+
+#[test]
+fn test_ring32_colliding_replicas() {
+    let nodes = [0, 1];
+    let mut ring = Ring32::with_hasher(util::ConstantState);
+    ring.insert_node(&nodes[0], 3);
+    ring.insert_node(&nodes[1], 3);
+    assert_eq!(ring.nodes[&0].len(), 6);
+    assert_eq!(ring.get_node(&0), &nodes[0]);
+
+    ring.insert_node(&nodes[0], 3);
+    assert_eq!(ring.nodes[&0].len(), 6);
+    ring.remove_node(&nodes[0]);
+    assert_eq!(ring.nodes[&0], vec![&nodes[1]; 3]);
+    assert_eq!(ring.get_node(&0), &nodes[1]);
+
+    ring.remove_node(&nodes[1]);
+    assert!(ring.nodes.is_empty());
+}
+
+#[test]
+fn test_nodes() {
+    let nodes = [0, 1, 2];
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, *node as usize + 1);
+    }
+
+    let mut entries: Vec<(&u64, usize)> = ring.nodes().collect();
+    entries.sort();
+    assert_eq!(entries, vec![(&0, 1), (&1, 2), (&2, 3)]);
+}
+
+#[test]
+fn test_update_replicas() {
+    let nodes = [0, 1];
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 3);
+    ring.insert_node(&nodes[1], 3);
+    let slots = ring.slots();
+
+    ring.update_replicas(&nodes[0], 5);
+    let updated = ring.slots();
+    assert_eq!(ring.replicas_of(&nodes[0]), Some(5));
+    assert_eq!(updated.len(), 8);
+    assert!(slots.iter().all(|slot| updated.contains(slot)));
+
+    ring.update_replicas(&nodes[0], 3);
+    assert_eq!(ring.slots(), slots);
+
+    ring.remove_replica(&nodes[1], 1);
+    ring.update_replicas(&nodes[1], 3);
+    assert_eq!(ring.slots(), slots);
+
+    ring.update_replicas(&nodes[0], 0);
+    assert_eq!(ring.replicas_of(&nodes[0]), None);
+    assert_eq!(ring.len(), 1);
+}
+
+#[test]
+fn test_get_node_bounded() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 100);
+    for node in &nodes[1..] {
+        ring.insert_node(node, 1);
+    }
+    let points: Vec<u64> = (0..1000).collect();
+    let unbounded = points.iter().filter(|point| ring.get_node(*point) == &nodes[0]).count();
+    assert!(unbounded > 125);
+
+    let assigned: Vec<&u64> = points
+        .iter()
+        .map(|point| ring.get_node_bounded(point, 1.25))
+        .collect();
+    assert_eq!(ring.get_node_bounded(&points[0], 1.25), assigned[0]);
+    let loads: Vec<u64> = nodes.iter().map(|node| ring.load_of(node)).collect();
+    assert_eq!(loads.iter().sum::<u64>(), 1000);
+    assert!(loads.iter().all(|load| *load <= 125));
+
+    for point in &points[..500] {
+        ring.release(point);
+    }
+    assert_eq!(ring.release(&points[0]), None);
+    assert_eq!(nodes.iter().map(|node| ring.load_of(node)).sum::<u64>(), 500);
+
+    ring.remove_node(assigned[999]);
+    assert_eq!(ring.load_of(assigned[999]), 0);
+    assert_ne!(ring.get_node_bounded(&points[999], 1.25), assigned[999]);
+}
+
+#[test]
+fn test_snapshot() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::with_hasher(util::SipState::new(1, 2));
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+    ring.remove_replica(&nodes[0], 3);
+    let snapshot = ring.snapshot();
+    let restored = Ring::from_snapshot(&snapshot);
+
+    assert_eq!(restored.slots(), ring.slots());
+    assert_eq!(restored.replicas_of(&nodes[0]), Some(9));
+    for point in 0..1000 {
+        assert_eq!(restored.get_node(&point), ring.get_node(&point));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serialize_snapshot() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::with_hasher(util::SipState::new(1, 2));
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+    let json = serde_json::to_string(&ring.snapshot()).unwrap();
+    let snapshot: Snapshot<u64, util::SipState> = serde_json::from_str(&json).unwrap();
+    let restored = Ring::from_snapshot(&snapshot);
+
+    for point in 0..1000 {
+        assert_eq!(restored.get_node(&point), ring.get_node(&point));
+    }
+}
+
+#[test]
+fn test_clear() {
+    let nodes = [0, 1, 2];
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 3);
+    }
+    ring.get_node_bounded(&0, 1.5);
+    ring.clear();
+    assert!(ring.is_empty());
+    assert_eq!(ring.len(), 0);
+    assert!(ring.slots().is_empty());
+    assert_eq!(ring.load_of(&nodes[0]), 0);
+
+    ring.insert_node(&nodes[0], 3);
+    assert_eq!(ring.get_node(&0), &nodes[0]);
+}
+
+#[test]
+fn test_get_node_batch() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let points: Vec<u64> = (0..1000).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+
+    let expected: Vec<&u64> = points.iter().map(|point| ring.get_node(point)).collect();
+    assert_eq!(ring.get_node_batch(&points), expected);
+}
+
+#[cfg(all(feature = "std", feature = "fast-hash"))]
+#[test]
+fn test_with_xxhash() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::with_xxhash();
+    let mut other = Ring::with_xxhash();
+    for node in &nodes {
+        ring.insert_node(node, 10);
+        other.insert_node(node, 10);
+    }
+
+    for point in 0..1000 {
+        assert_eq!(ring.get_node(&point), other.get_node(&point));
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_owned_ring() {
+    let hash_builder = RandomState::new();
+    let mut ring = OwnedRing::with_hasher(hash_builder.clone());
+    let expected: Vec<String> = {
+        let ids: Vec<String> = (0..10).map(|node| format!("node-{}", node)).collect();
+        let mut borrowed = Ring::with_hasher(hash_builder);
+        for id in &ids {
+            borrowed.insert_node(id, 10);
+            ring.insert_node(id.clone(), 10);
+        }
+        (0..100u64).map(|point| borrowed.get_node(&point).clone()).collect()
+    };
+
+    assert_eq!(ring.len(), 10);
+    for (point, id) in (0..100u64).zip(&expected) {
+        assert_eq!(ring.get_node(&point), id);
+    }
+
+    ring.insert_node(expected[0].clone(), 5);
+    assert_eq!(ring.len(), 10);
+    assert_eq!(ring.nodes.values().map(Vec::len).sum::<usize>(), 95);
+    ring.remove_node(&expected[0]);
+    assert_eq!(ring.len(), 9);
+    assert!(ring.nodes().all(|(id, replicas)| *id != expected[0] && replicas == 10));
+}
+
+#[test]
+fn test_contains_node() {
+    let nodes = [0, 1];
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 3);
+
+    assert!(ring.contains_node(&nodes[0]));
+    assert!(!ring.contains_node(&nodes[1]));
+    ring.remove_node(&nodes[0]);
+    assert!(!ring.contains_node(&nodes[0]));
+}
+
+#[test]
+fn test_node_positions() {
+    let nodes = [0, 1, 2];
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 10);
+    ring.insert_node(&nodes[1], 5);
+
+    let positions = ring.node_positions(&nodes[0]);
+    assert_eq!(positions.len(), 10);
+    assert!(positions.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(positions.iter().all(|position| ring.nodes[position].contains(&&nodes[0])));
+    assert_eq!(ring.node_positions(&nodes[1]).len(), 5);
+    assert!(ring.node_positions(&nodes[2]).is_empty());
+
+    let mut ring = Ring::with_hasher(util::ConstantState);
+    ring.insert_node(&nodes[0], 3);
+    ring.insert_node(&nodes[1], 2);
+    assert_eq!(ring.node_positions(&nodes[0]), vec![0, 0, 0]);
+    assert_eq!(ring.node_positions(&nodes[1]), vec![0, 0]);
+}
+
+#[test]
+fn test_get_node_with_position() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    assert_eq!(ring.get_node_with_position(&0), None);
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+
+    let first = *ring.nodes.keys().next().expect("Expected non-empty ring.");
+    for point in 0..1000 {
+        let (node, position) = ring.get_node_with_position(&point).expect("Expected a node.");
+        let point_hash = util::gen_hash(&ring.hash_builder, &point);
+        assert_eq!(node, ring.get_node(&point));
+        assert!(ring.nodes[&position].contains(&node));
+        if position >= point_hash {
+            assert!(ring.nodes.range(point_hash..position).next().is_none());
+        } else {
+            assert_eq!(position, first);
+            assert!(ring.nodes.range(point_hash..).next().is_none());
+        }
+    }
+}
+
+#[test]
+fn test_keys_for_node() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let keys: Vec<u64> = (0..1000).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 10);
+    }
+
+    let mut assigned: Vec<u64> = Vec::new();
+    for node in &nodes {
+        let node_keys = ring.keys_for_node(node, &keys);
+        assert!(node_keys.iter().all(|key| ring.get_node(*key) == node));
+        assigned.extend(node_keys);
+    }
+    assigned.sort_unstable();
+    assert_eq!(assigned, keys);
+    assert!(ring.keys_for_node(&10, &keys).is_empty());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_ring_builder() {
+    let nodes = [0, 1, 2];
+    let hash_builder = RandomState::new();
+    let built = RingBuilder::new()
+        .hasher(hash_builder.clone())
+        .node(&nodes[0], 3)
+        .node(&nodes[1], 5)
+        .nodes(vec![(&nodes[2], 2)])
+        .build();
+    let mut ring = Ring::with_hasher(hash_builder);
+    ring.insert_node(&nodes[0], 3);
+    ring.insert_node(&nodes[1], 5);
+    ring.insert_node(&nodes[2], 2);
+
+    assert_eq!(built.slots(), ring.slots());
+    for point in 0..1000 {
+        assert_eq!(built.get_node(&point), ring.get_node(&point));
     }
 }
@@ -1,8 +1,10 @@
 // import commonly used items from the prelude:
 use std::fmt;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rand::distributions::{Uniform, Distribution};
-use rand_distr::{Normal, LogNormal};
+use rand::distributions::Alphanumeric;
+use rand_distr::{Normal, LogNormal, Pareto, Zipf};
 
 const MAX_KEY: f64 = core::f64::MAX;
 
@@ -11,6 +13,8 @@ pub enum KeyDistribution {
     UNIFORM(rand::distributions::Uniform<f64>),
     NORMAL(rand_distr::Normal<f64>),
     LOGNORMAL(rand_distr::LogNormal<f64>),
+    ZIPF(rand_distr::Zipf<f64>),
+    PARETO(rand_distr::Pareto<f64>),
 }
 
 impl fmt::Display for KeyDistribution {
@@ -19,6 +23,8 @@ impl fmt::Display for KeyDistribution {
             KeyDistribution::UNIFORM(_) => write!(f, "uniform"),
             KeyDistribution::NORMAL(_) => write!(f, "normal"),
             KeyDistribution::LOGNORMAL(_) => write!(f, "lognormal"),
+            KeyDistribution::ZIPF(_) => write!(f, "zipf"),
+            KeyDistribution::PARETO(_) => write!(f, "pareto"),
         }
     }
 }
@@ -35,18 +41,38 @@ impl KeyDistribution {
     pub fn lognormal_distribution() -> KeyDistribution {
         KeyDistribution::LOGNORMAL(LogNormal::new(8.0, 1.0).unwrap())
     }
+
+    /// A Zipf distribution over the keys `1..=n`, where the frequency of the key with rank `k` is
+    /// proportional to `k^-s`.
+    pub fn zipf(n: u64, s: f64) -> KeyDistribution {
+        KeyDistribution::ZIPF(Zipf::new(n, s).unwrap())
+    }
+
+    /// A Pareto distribution with a minimum of `scale` and a tail index of `shape`.
+    pub fn pareto(scale: f64, shape: f64) -> KeyDistribution {
+        KeyDistribution::PARETO(Pareto::new(scale, shape).unwrap())
+    }
 }
 
 /// A random number generator
 pub struct Generator {
-    rand: rand::rngs::ThreadRng,
+    rand: StdRng,
     dis: KeyDistribution,
 }
 
 impl Generator {
     pub fn new(dis: KeyDistribution) -> Self {
         Self {
-            rand: rand::thread_rng(),
+            rand: StdRng::from_entropy(),
+            dis,
+        }
+    }
+
+    /// Constructs a new `Generator` whose random number generator is seeded with `seed`, so
+    /// generators with the same seed and distribution produce the same keys.
+    pub fn with_seed(dis: KeyDistribution, seed: u64) -> Self {
+        Self {
+            rand: StdRng::seed_from_u64(seed),
             dis,
         }
     }
@@ -56,6 +82,23 @@ impl Generator {
             .map(|_| { self.next().unwrap() })
             .collect()
     }
+
+    /// Returns a random alphanumeric key of length `len`. The key distribution of the generator
+    /// is not used.
+    pub fn next_string(&mut self, len: usize) -> String {
+        (&mut self.rand)
+            .sample_iter(Alphanumeric)
+            .take(len)
+            .map(char::from)
+            .collect()
+    }
+
+    /// Returns `n` random alphanumeric keys of length `len`.
+    pub fn next_n_strings(&mut self, n: u64, len: usize) -> Vec<String> {
+        (0..n)
+            .map(|_| { self.next_string(len) })
+            .collect()
+    }
 }
 
 impl Iterator for Generator {
@@ -65,7 +108,9 @@ impl Iterator for Generator {
         let r = match self.dis {
             KeyDistribution::UNIFORM(x) => self.rand.sample(x).floor(),
             KeyDistribution::NORMAL(x) => self.rand.sample(x).floor(),
-            KeyDistribution::LOGNORMAL(x) => self.rand.sample(x).floor()
+            KeyDistribution::LOGNORMAL(x) => self.rand.sample(x).floor(),
+            KeyDistribution::ZIPF(x) => self.rand.sample(x).floor(),
+            KeyDistribution::PARETO(x) => self.rand.sample(x).floor(),
         };
 
         Some(r as Self::Item)
@@ -77,6 +122,8 @@ fn test_distribution_display() {
     let uniform = KeyDistribution::uniform_distribution();
 
     assert_eq!(format!("{:}", uniform), "uniform");
+    assert_eq!(format!("{:}", KeyDistribution::zipf(100, 1.5)), "zipf");
+    assert_eq!(format!("{:}", KeyDistribution::pareto(1.0, 2.0)), "pareto");
 }
 
 #[test]
@@ -87,3 +134,41 @@ fn test_iterator() {
 
     assert_eq!(workload.len(), num_items as usize);
 }
+
+#[test]
+fn test_with_seed() {
+    let num_items :u64 = 1000;
+    let mut key_generator = Generator::with_seed(KeyDistribution::lognormal_distribution(), 7);
+    let mut other_generator = Generator::with_seed(KeyDistribution::lognormal_distribution(), 7);
+
+    assert_eq!(key_generator.next_n(num_items), other_generator.next_n(num_items));
+}
+
+#[test]
+fn test_next_n_strings() {
+    let mut key_generator = Generator::with_seed(KeyDistribution::uniform_distribution(), 7);
+    let keys = key_generator.next_n_strings(100, 16);
+
+    assert_eq!(keys.len(), 100);
+    for key in &keys {
+        assert_eq!(key.len(), 16);
+        assert!(key.chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+}
+
+#[test]
+fn test_zipf() {
+    let num_items :u64 = 100_000;
+    let mut key_generator = Generator::new(KeyDistribution::zipf(100, 1.5));
+    let workload: Vec<u64> = key_generator.next_n(num_items);
+
+    let mut counts = vec![0u64; 101];
+    for key in &workload {
+        counts[*key as usize] += 1;
+    }
+    // The frequency of the first key is `1 / sum(k^-1.5)`, which is approximately 0.414.
+    let first = counts[1] as f64 / num_items as f64;
+    assert_eq!(counts[0], 0);
+    assert!((first - 0.414).abs() < 0.02);
+    assert!(counts[2..].iter().all(|count| *count < counts[1]));
+}
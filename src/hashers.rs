@@ -0,0 +1,93 @@
+//! Non-cryptographic hash builders that are faster than the default `RandomState`.
+//!
+//! The module is enabled by the `fast-hash` feature. The hash builders are not keyed, so they
+//! should not be used for keys that are controlled by an adversary.
+
+use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+use twox_hash::XxHash64;
+
+// The finalizer of MurmurHash3.
+fn fmix64(value: u64) -> u64 {
+    let mut z = value;
+    z = (z ^ (z >> 33)).wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    z = (z ^ (z >> 33)).wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+    z ^ (z >> 33)
+}
+
+/// A hash builder that hashes with 64-bit FNV-1a and finalizes the hash with the finalizer of
+/// MurmurHash3.
+///
+/// FNV mixes its last bytes poorly into the high bits of the hash, so without the finalizer,
+/// rendezvous hashing, which compares whole hashes, would favor some nodes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FnvState;
+
+/// The hasher built by `FnvState`.
+#[derive(Default)]
+pub struct FnvHasher(fnv::FnvHasher);
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        fmix64(self.0.finish())
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+}
+
+impl BuildHasher for FnvState {
+    type Hasher = FnvHasher;
+
+    fn build_hasher(&self) -> FnvHasher {
+        FnvHasher::default()
+    }
+}
+
+/// A hash builder that hashes with 64-bit xxHash and a seed of zero.
+pub type XxHashState = BuildHasherDefault<XxHash64>;
+
+// Checks that 100k keys are spread evenly over ten nodes of a consistent ring, which hashes with
+// `gen_hash`, and of a rendezvous ring, which hashes with `combine_hash`.
+#[cfg(test)]
+fn assert_balanced<H>(hash_builder: H)
+    where
+        H: std::hash::BuildHasher + Default + Clone,
+{
+    use crate::analysis;
+    use crate::{consistent, rendezvous};
+
+    fn max_error(loads: Vec<(&u64, u64)>, total: u64) -> f64 {
+        let loads = loads.into_iter().collect();
+        analysis::load_errors(&loads, total)
+            .values()
+            .cloned()
+            .fold(0.0, f64::max)
+    }
+
+    let nodes: Vec<u64> = (0..10).collect();
+    let keys: Vec<u64> = (0..100_000).collect();
+    let mut consistent_ring = consistent::Ring::with_hasher(hash_builder.clone());
+    let mut rendezvous_ring = rendezvous::Ring::with_hasher(hash_builder);
+    for node in &nodes {
+        consistent_ring.insert_node(node, 200);
+        rendezvous_ring.insert_node(node, 1);
+    }
+
+    let consistent_loads = analysis::simulate_distribution(&consistent_ring, &keys);
+    let rendezvous_loads = analysis::simulate_distribution(&rendezvous_ring, &keys);
+    assert_eq!(consistent_loads.len(), nodes.len());
+    assert_eq!(rendezvous_loads.len(), nodes.len());
+    assert!(max_error(consistent_loads, keys.len() as u64) < 0.25);
+    assert!(max_error(rendezvous_loads, keys.len() as u64) < 0.05);
+}
+
+#[test]
+fn test_xxhash_distribution() {
+    assert_balanced(XxHashState::default());
+}
+
+#[test]
+fn test_fnv_distribution() {
+    assert_balanced(FnvState);
+}
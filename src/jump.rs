@@ -1,6 +1,14 @@
-use crate::util;
+use crate::util::{self, HashedPoint};
+use crate::{HashRing, HashRingError, Router};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::hash::{BuildHasher, Hash};
+use core::mem;
+#[cfg(all(test, feature = "std"))]
 use std::collections::hash_map::RandomState;
-use std::hash::{BuildHasher, Hash};
+
+const LCG_MULTIPLIER: u64 = 2_862_933_555_777_941_757;
+const LCG_INCREMENT: u64 = 1;
 
 /// A hashing ring implemented using jump hashing.
 ///
@@ -10,50 +18,807 @@ use std::hash::{BuildHasher, Hash};
 ///
 /// Jump hashing is very fast and executes in `O(ln n)` time. It also has no memory overhead and has
 /// virtually perfect key distribution. However, the main limitation of jump hashing is that it
-/// returns an integer in the range [0, nodes) and it does not support arbitrary node names.
-pub struct Ring<H = RandomState> {
+/// returns an integer in the range [0, nodes) and it does not support arbitrary node names. A
+/// `LabeledRing` or a `NamedRing` can be used to map the integers to node names, and a
+/// `WeightedRing` to map them to weighted node names.
+pub struct Ring<H = util::DefaultState> {
     nodes: u32,
+    multiplier: u64,
+    increment: u64,
     hash_builder: H,
 }
 
-impl Ring<RandomState> {
+impl Ring<util::DefaultState> {
     /// Constructs a new `Ring` with a specified number of nodes.
     pub fn new(nodes: u32) -> Self {
         Self::with_hasher(Default::default(), nodes)
     }
+
+    /// Constructs a new `Ring` with a specified number of nodes that uses a linear congruential
+    /// generator with a specified multiplier and increment to jump between nodes.
+    ///
+    /// The default generator uses a multiplier of `2862933555777941757` and an increment of `1`.
+    /// By the Hull-Dobell theorem, the generator has a full period modulo `2^64` if and only if the
+    /// multiplier is congruent to 1 modulo 4 and the increment is odd.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the multiplier is not congruent to 1 modulo 4 or if the increment is even.
+    pub fn with_lcg(nodes: u32, multiplier: u64, increment: u64) -> Self {
+        assert!(multiplier % 4 == 1, "Error: LCG multiplier must be congruent to 1 modulo 4.");
+        assert!(increment % 2 == 1, "Error: LCG increment must be odd.");
+        Self {
+            multiplier,
+            increment,
+            ..Self::new(nodes)
+        }
+    }
+}
+
+impl Ring<util::IntegerState> {
+    /// Returns the nodes associated with a slice of `u64` keys.
+    ///
+    /// The keys are mixed in a single pass before jumping, which avoids constructing a hasher for
+    /// every key. The result is the same as calling `get_node` for every key.
+    pub fn get_nodes_u64(&self, keys: &[u64]) -> Vec<u32> {
+        let hashes: Vec<u64> = keys.iter().map(|key| util::mix_u64(*key)).collect();
+        hashes.into_iter().map(|hash| self.jump(hash)).collect()
+    }
 }
 
 impl<H> Ring<H> {
     /// Constructs a new `Ring` with a specified number of nodes and hash builder.
+    ///
+    /// A ring with no nodes can be constructed, but keys can only be looked up in it with
+    /// `try_get_node`.
     pub fn with_hasher(hash_builder: H, nodes: u32) -> Self {
-        assert!(nodes >= 1);
         Self {
             hash_builder,
+            multiplier: LCG_MULTIPLIER,
+            increment: LCG_INCREMENT,
             nodes,
         }
     }
 
+    /// Adds a node to the end of the ring.
+    ///
+    /// Only the keys that are mapped to the new node move, which is approximately `1 / nodes` of
+    /// the keys.
+    pub fn grow(&mut self) {
+        self.nodes += 1;
+    }
+
+    /// Removes the node at the end of the ring. Only the keys that were mapped to the removed node
+    /// move.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring has fewer than two nodes.
+    pub fn shrink(&mut self) {
+        assert!(self.nodes > 1, "Error: cannot remove the last node.");
+        self.nodes -= 1;
+    }
+
     /// Returns the node associated with a key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring has no nodes.
     pub fn get_node<T>(&self, key: &T) -> u32
     where
         T: Hash,
         H: BuildHasher,
     {
-        let mut h = util::gen_hash(&self.hash_builder, key);
+        self.get_node_prehashed(&util::hash_point(&self.hash_builder, key))
+    }
+
+    /// Returns the node associated with a key, or `HashRingError::EmptyRing` if the ring has no
+    /// nodes.
+    pub fn try_get_node<T>(&self, key: &T) -> Result<u32, HashRingError>
+    where
+        T: Hash,
+        H: BuildHasher,
+    {
+        if self.nodes == 0 {
+            Err(HashRingError::EmptyRing)
+        } else {
+            Ok(self.get_node(key))
+        }
+    }
+
+    /// Returns the number of keys that are mapped to each node, indexed by node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring has no nodes and `keys` is not empty.
+    pub fn distribution<T>(&self, keys: &[T]) -> Vec<u64>
+    where
+        T: Hash,
+        H: BuildHasher,
+    {
+        let mut counts = vec![0; self.nodes as usize];
+        for key in keys {
+            counts[self.get_node(key) as usize] += 1;
+        }
+        counts
+    }
+
+    /// Returns the node associated with a key that was hashed by `util::hash_point` with the same
+    /// hash builder as the ring.
+    pub fn get_node_prehashed(&self, key: &HashedPoint) -> u32 {
+        self.jump(key.value())
+    }
+
+    fn jump(&self, h: u64) -> u32 {
+        self.jump_with_state(h).0
+    }
+
+    // Returns the node and the final state of the generator.
+    fn jump_with_state(&self, mut h: u64) -> (u32, u64) {
+        if self.nodes == 0 {
+            panic!("Error: empty ring.");
+        }
         let mut i: i64 = -1;
         let mut j: i64 = 0;
 
         while j < i64::from(self.nodes) {
             i = j;
-            h = h.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+            h = h.wrapping_mul(self.multiplier).wrapping_add(self.increment);
             j = (((i.wrapping_add(1)) as f64) * ((1i64 << 31) as f64)
                 / (((h >> 33).wrapping_add(1)) as f64)) as i64;
         }
-        i as u32
+        (i as u32, h)
+    }
+
+    /// Returns the node associated with a key and a position for the key in `[0, 1)`.
+    ///
+    /// The position is derived from the final state of the generator that jumps between nodes.
+    /// Jumping stops once the upper 31 bits of the state are below `(node + 1) * 2^31 / nodes`, so
+    /// the state divided by that bound is uniformly distributed in `[0, 1)` for the keys of a node.
+    /// A node can therefore be split further into ordered ranges by position. The position of a
+    /// key depends on the number of nodes, so it may change when nodes are added or removed.
+    pub fn get_bucket_with_position<K>(&self, key: &K) -> (u32, f64)
+    where
+        K: Hash,
+        H: BuildHasher,
+    {
+        let (node, state) = self.jump_with_state(util::gen_hash(&self.hash_builder, key));
+        let bound = f64::from(node + 1) * (1u64 << 31) as f64 / f64::from(self.nodes);
+        let position = state as f64 / (1u64 << 33) as f64 / bound;
+        (node, position.min(1.0 - f64::EPSILON))
+    }
+
+    /// Returns the number of bytes used by the ring. Jump hashing stores no state per node, so this
+    /// is the size of the ring itself regardless of the number of nodes.
+    pub fn memory_footprint(&self) -> usize {
+        mem::size_of::<Self>()
     }
 
     /// Returns the number of nodes in the ring.
     pub fn nodes(&self) -> u32 {
         self.nodes
     }
-}
\ No newline at end of file
+}
+
+/// A hashing ring implemented using jump hashing that maps keys to labels borrowed from the
+/// caller.
+///
+/// The ring has one node for each label, and a key is mapped to the label at the index returned by
+/// jump hashing. The ring does not allocate, so it retains the memory characteristics of `Ring`.
+pub struct LabeledRing<'a, T, H = util::DefaultState> {
+    labels: &'a [T],
+    ring: Ring<H>,
+}
+
+impl<'a, T> LabeledRing<'a, T, util::DefaultState> {
+    /// Constructs a new `LabeledRing<T>` with a node for each of the labels.
+    pub fn new(labels: &'a [T]) -> Self {
+        Self::with_hasher(Default::default(), labels)
+    }
+}
+
+impl<'a, T, H> LabeledRing<'a, T, H> {
+    /// Constructs a new `LabeledRing<T>` with a node for each of the labels and a specified hash
+    /// builder.
+    pub fn with_hasher(hash_builder: H, labels: &'a [T]) -> Self {
+        Self {
+            labels,
+            ring: Ring::with_hasher(hash_builder, labels.len() as u32),
+        }
+    }
+
+    /// Returns the label of the node associated with a key.
+    pub fn get_node<U>(&self, key: &U) -> &'a T
+    where
+        U: Hash,
+        H: BuildHasher,
+    {
+        &self.labels[self.ring.get_node(key) as usize]
+    }
+
+    /// Returns the labels of the nodes in the ring.
+    pub fn labels(&self) -> &'a [T] {
+        self.labels
+    }
+
+    /// Returns the number of nodes in the ring.
+    pub fn nodes(&self) -> u32 {
+        self.ring.nodes()
+    }
+}
+
+impl<'a, T, U, H> HashRing<'a, T, U> for LabeledRing<'a, T, H>
+where
+    U: Hash,
+    H: BuildHasher,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.labels.iter().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
+
+/// A hashing ring implemented using jump hashing that maps keys to a list of node ids owned by the
+/// ring.
+///
+/// Unlike `LabeledRing`, nodes can be appended to and removed from the ring. Appending a node adds
+/// a node to the end of the underlying `Ring`, so only the keys that are mapped to the new node
+/// move. Jump hashing can only remove the last node cleanly, so removing any other node shifts the
+/// nodes after it and reshuffles the keys that are mapped to them.
+pub struct NamedRing<'a, T, H = util::DefaultState> {
+    ids: Vec<&'a T>,
+    ring: Ring<H>,
+}
+
+impl<'a, T> NamedRing<'a, T, util::DefaultState> {
+    /// Constructs a new `NamedRing<T>` with the specified nodes.
+    pub fn new(ids: Vec<&'a T>) -> Self {
+        Self::with_hasher(Default::default(), ids)
+    }
+}
+
+impl<'a, T, H> NamedRing<'a, T, H> {
+    /// Constructs a new `NamedRing<T>` with the specified nodes and hash builder.
+    pub fn with_hasher(hash_builder: H, ids: Vec<&'a T>) -> Self {
+        Self {
+            ring: Ring::with_hasher(hash_builder, ids.len() as u32),
+            ids,
+        }
+    }
+
+    /// Appends a node to the end of the ring.
+    pub fn push(&mut self, id: &'a T) {
+        self.ids.push(id);
+        self.ring.grow();
+    }
+
+    /// Removes a node from the ring.
+    ///
+    /// If the node is not the last node of the ring, the nodes after it are shifted, so the keys
+    /// that are mapped to them are reshuffled as well.
+    pub fn remove_node(&mut self, id: &T)
+    where
+        T: Eq,
+    {
+        if let Some(index) = self.ids.iter().position(|existing_id| *existing_id == id) {
+            self.ids.remove(index);
+            self.ring.nodes = self.ids.len() as u32;
+        }
+    }
+
+    /// Returns the node associated with a key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring is empty.
+    pub fn get_node<U>(&self, key: &U) -> &'a T
+    where
+        U: Hash,
+        H: BuildHasher,
+    {
+        self.ids[self.ring.get_node(key) as usize]
+    }
+
+    /// Returns the nodes of the ring in order.
+    pub fn ids(&self) -> &[&'a T] {
+        &self.ids
+    }
+
+    /// Returns the number of nodes in the ring.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns `true` if the ring is empty.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+impl<'a, T, U, H> HashRing<'a, T, U> for NamedRing<'a, T, H>
+where
+    U: Hash,
+    H: BuildHasher,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.ids.clone()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+/// A hashing ring implemented using jump hashing that maps keys to weighted node ids.
+///
+/// Each node owns a run of consecutive nodes of the underlying `Ring`, one for each unit of its
+/// weight, and a key is mapped to the node that owns the index returned by jump hashing. The number
+/// of points mapped to a node is proportional to its weight. Appending a node only moves the keys
+/// that are mapped to its new indices, and removing the last node only moves the keys that were
+/// mapped to it.
+pub struct WeightedRing<'a, T, H = util::DefaultState> {
+    nodes: Vec<(&'a T, u32)>,
+    // The exclusive upper bound of the indices owned by each node.
+    bounds: Vec<u32>,
+    ring: Ring<H>,
+}
+
+impl<'a, T> WeightedRing<'a, T, util::DefaultState> {
+    /// Constructs a new `WeightedRing<T>` with the specified nodes and weights.
+    pub fn new(nodes: Vec<(&'a T, u32)>) -> Self {
+        Self::with_hasher(Default::default(), nodes)
+    }
+}
+
+impl<'a, T, H> WeightedRing<'a, T, H> {
+    /// Constructs a new `WeightedRing<T>` with the specified nodes, weights and hash builder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a weight is zero or if the total weight of the nodes does not fit in a `u32`.
+    pub fn with_hasher(hash_builder: H, nodes: Vec<(&'a T, u32)>) -> Self {
+        let mut ret = Self {
+            nodes,
+            bounds: Vec::new(),
+            ring: Ring::with_hasher(hash_builder, 0),
+        };
+        ret.rebuild();
+        ret
+    }
+
+    fn rebuild(&mut self) {
+        let mut total = 0u32;
+        self.bounds = self
+            .nodes
+            .iter()
+            .map(|(_, weight)| {
+                assert!(*weight > 0, "Error: weight must be positive.");
+                total = total
+                    .checked_add(*weight)
+                    .expect("Error: total weight must fit in a u32.");
+                total
+            })
+            .collect();
+        self.ring.nodes = total;
+    }
+
+    /// Appends a node with a particular weight to the end of the ring.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the weight is zero or if the total weight of the nodes would not fit in a `u32`.
+    pub fn push(&mut self, id: &'a T, weight: u32) {
+        self.nodes.push((id, weight));
+        self.rebuild();
+    }
+
+    /// Removes the last node of the ring and returns it with its weight, or `None` if the ring is
+    /// empty.
+    pub fn pop(&mut self) -> Option<(&'a T, u32)> {
+        let node = self.nodes.pop();
+        self.rebuild();
+        node
+    }
+
+    /// Returns the node associated with a key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring is empty.
+    pub fn get_node<U>(&self, key: &U) -> &'a T
+    where
+        U: Hash,
+        H: BuildHasher,
+    {
+        let index = self.ring.get_node(key);
+        self.nodes[self.bounds.partition_point(|bound| *bound <= index)].0
+    }
+
+    /// Returns the nodes of the ring and their weights in order.
+    pub fn nodes(&self) -> &[(&'a T, u32)] {
+        &self.nodes
+    }
+
+    /// Returns the number of nodes in the ring.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the ring is empty.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl<'a, T, U, H> HashRing<'a, T, U> for WeightedRing<'a, T, H>
+where
+    U: Hash,
+    H: BuildHasher,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.nodes.iter().map(|node| node.0).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<'a, T, H> Router for WeightedRing<'a, T, H>
+where
+    H: BuildHasher,
+{
+    type Output = &'a T;
+
+    fn route<K>(&self, key: &K) -> &'a T
+    where
+        K: Hash,
+    {
+        self.get_node(key)
+    }
+}
+
+/// A sharding facade implemented using jump hashing that maps a key to a primary shard and a number
+/// of follower shards.
+///
+/// The primary shard of a key is the node returned by `Ring`. Each follower shard is found by
+/// jump hashing the key combined with an attempt counter and skipping shards that were already
+/// chosen, so the followers are distinct from the primary and from each other, and the same key
+/// always yields the same shards in the same order.
+pub struct Sharder<H = util::DefaultState> {
+    ring: Ring<H>,
+}
+
+impl Sharder<util::DefaultState> {
+    /// Constructs a new `Sharder` with a specified number of shards.
+    pub fn new(num_shards: u32) -> Self {
+        Self::with_hasher(Default::default(), num_shards)
+    }
+}
+
+impl<H> Sharder<H> {
+    /// Constructs a new `Sharder` with a specified number of shards and hash builder.
+    pub fn with_hasher(hash_builder: H, num_shards: u32) -> Self {
+        Self {
+            ring: Ring::with_hasher(hash_builder, num_shards),
+        }
+    }
+
+    /// Returns the primary shard associated with a key.
+    pub fn get_shard<T>(&self, key: &T) -> u32
+    where
+        T: Hash,
+        H: BuildHasher,
+    {
+        self.ring.get_node(key)
+    }
+
+    /// Returns the primary shard associated with a key and `replica_count` distinct follower
+    /// shards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `replica_count` is not less than the number of shards.
+    pub fn get_shards<T>(&self, key: &T, replica_count: usize) -> (u32, Vec<u32>)
+    where
+        T: Hash,
+        H: BuildHasher,
+    {
+        assert!(
+            replica_count < self.ring.nodes() as usize,
+            "Error: not enough shards for replicas."
+        );
+        let point_hash = util::gen_hash(&self.ring.hash_builder, key);
+        let primary = self.ring.jump(point_hash);
+        let mut replicas = Vec::with_capacity(replica_count);
+        let mut attempt = 0u64;
+        while replicas.len() < replica_count {
+            attempt += 1;
+            let shard = self.ring.jump(util::combine_hash(
+                &self.ring.hash_builder,
+                point_hash,
+                attempt,
+            ));
+            if shard != primary && !replicas.contains(&shard) {
+                replicas.push(shard);
+            }
+        }
+        (primary, replicas)
+    }
+
+    /// Returns the number of shards.
+    pub fn shards(&self) -> u32 {
+        self.ring.nodes()
+    }
+}
+
+impl<H> Router for Ring<H>
+where
+    H: BuildHasher,
+{
+    type Output = u32;
+
+    fn route<K>(&self, key: &K) -> u32
+    where
+        K: Hash,
+    {
+        self.get_node(key)
+    }
+}
+
+impl<'a, T, H> Router for LabeledRing<'a, T, H>
+where
+    H: BuildHasher,
+{
+    type Output = &'a T;
+
+    fn route<K>(&self, key: &K) -> &'a T
+    where
+        K: Hash,
+    {
+        self.get_node(key)
+    }
+}
+
+impl<'a, T, H> Router for NamedRing<'a, T, H>
+where
+    H: BuildHasher,
+{
+    type Output = &'a T;
+
+    fn route<K>(&self, key: &K) -> &'a T
+    where
+        K: Hash,
+    {
+        self.get_node(key)
+    }
+}
+
+#[test]
+fn test_try_get_node() {
+    let ring = Ring::new(10);
+    for key in 0..100 {
+        assert_eq!(ring.try_get_node(&key), Ok(ring.get_node(&key)));
+    }
+
+    let empty_ring = Ring::new(0);
+    assert_eq!(empty_ring.nodes(), 0);
+    assert_eq!(empty_ring.try_get_node(&0), Err(HashRingError::EmptyRing));
+}
+
+#[test]
+fn test_get_nodes_u64() {
+    let ring = Ring::with_hasher(util::IntegerState, 10);
+    let keys: Vec<u64> = (0..1000).collect();
+
+    let expected: Vec<u32> = keys.iter().map(|key| ring.get_node(key)).collect();
+    assert_eq!(ring.get_nodes_u64(&keys), expected);
+}
+
+#[test]
+fn test_with_lcg() {
+    let ring = Ring::new(10);
+    let mut standard = Ring::with_lcg(10, LCG_MULTIPLIER, LCG_INCREMENT);
+    standard.hash_builder = ring.hash_builder.clone();
+    let mut custom = Ring::with_lcg(10, 6_364_136_223_846_793_005, 1_442_695_040_888_963_407);
+    custom.hash_builder = ring.hash_builder.clone();
+
+    let mut differences = 0;
+    for key in 0..1000 {
+        assert_eq!(standard.get_node(&key), ring.get_node(&key));
+        assert!(custom.get_node(&key) < 10);
+        if custom.get_node(&key) != ring.get_node(&key) {
+            differences += 1;
+        }
+    }
+    assert!(differences > 0);
+}
+
+#[test]
+#[should_panic]
+fn test_with_lcg_even_multiplier() {
+    Ring::with_lcg(10, 2, 1);
+}
+
+#[test]
+#[should_panic]
+fn test_with_lcg_multiplier_not_one_mod_four() {
+    Ring::with_lcg(10, 3, 1);
+}
+
+#[test]
+#[should_panic]
+fn test_with_lcg_even_increment() {
+    Ring::with_lcg(10, LCG_MULTIPLIER, 2);
+}
+
+#[test]
+fn test_get_bucket_with_position() {
+    let ring = Ring::new(10);
+    let mut total = 0.0;
+
+    for key in 0..10_000 {
+        let (bucket, position) = ring.get_bucket_with_position(&key);
+        assert_eq!(bucket, ring.get_node(&key));
+        assert!((0.0..1.0).contains(&position));
+        total += position;
+    }
+    assert!((total / 10_000.0 - 0.5).abs() < 0.05);
+}
+
+#[test]
+fn test_sharder() {
+    let sharder = Sharder::new(10);
+
+    for key in 0..1000 {
+        let (primary, replicas) = sharder.get_shards(&key, 3);
+        assert_eq!(primary, sharder.get_shard(&key));
+        assert_eq!(replicas.len(), 3);
+        assert!(!replicas.contains(&primary));
+        assert!(replicas.iter().all(|shard| *shard < 10));
+        for (i, shard) in replicas.iter().enumerate() {
+            assert!(!replicas[i + 1..].contains(shard));
+        }
+        assert_eq!(sharder.get_shards(&key, 3), (primary, replicas));
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_labeled_ring() {
+    let labels = ["alpha", "beta", "gamma", "delta"];
+    let ring = LabeledRing::with_hasher(RandomState::new(), &labels);
+    let indices = Ring::with_hasher(ring.ring.hash_builder.clone(), 4);
+
+    assert_eq!(ring.nodes(), 4);
+    for key in 0..1000 {
+        assert_eq!(ring.get_node(&key), &labels[indices.get_node(&key) as usize]);
+    }
+}
+
+#[test]
+fn test_grow_and_shrink() {
+    let keys: Vec<u64> = (0..10_000).collect();
+    let mut ring = Ring::new(10);
+    let before: Vec<u32> = keys.iter().map(|key| ring.get_node(key)).collect();
+
+    ring.grow();
+    assert_eq!(ring.nodes(), 11);
+    let after: Vec<u32> = keys.iter().map(|key| ring.get_node(key)).collect();
+    assert!(before
+        .iter()
+        .zip(&after)
+        .all(|(old, new)| old == new || *new == 10));
+    let moved = after.iter().filter(|node| **node == 10).count();
+    assert!(moved > 700 && moved < 1100);
+
+    ring.shrink();
+    assert_eq!(ring.nodes(), 10);
+    assert!(keys.iter().zip(&before).all(|(key, node)| ring.get_node(key) == *node));
+}
+
+#[test]
+#[should_panic]
+fn test_shrink_last_node() {
+    let mut ring = Ring::new(1);
+    ring.shrink();
+}
+
+#[test]
+fn test_distribution() {
+    let keys: Vec<u64> = (0..100_000).collect();
+    let ring = Ring::new(10);
+    let counts = ring.distribution(&keys);
+
+    assert_eq!(counts.len(), 10);
+    assert_eq!(counts.iter().sum::<u64>(), keys.len() as u64);
+    for count in &counts {
+        assert!((*count as f64 / 10_000.0 - 1.0).abs() < 0.05);
+    }
+    assert!(Ring::new(0).distribution::<u64>(&[]).is_empty());
+}
+
+#[test]
+fn test_named_ring() {
+    let ids = ["alpha", "beta", "gamma", "delta", "epsilon"];
+    let keys: Vec<u64> = (0..10_000).collect();
+    let mut ring = NamedRing::new(ids[..4].iter().collect());
+    let indices = Ring::with_hasher(ring.ring.hash_builder.clone(), 4);
+    let before: Vec<&&str> = keys.iter().map(|key| ring.get_node(key)).collect();
+    assert!(keys
+        .iter()
+        .zip(&before)
+        .all(|(key, id)| **id == ids[indices.get_node(key) as usize]));
+
+    ring.push(&ids[4]);
+    assert_eq!(ring.len(), 5);
+    assert!(keys
+        .iter()
+        .zip(&before)
+        .all(|(key, id)| ring.get_node(key) == *id || ring.get_node(key) == &ids[4]));
+
+    ring.remove_node(&ids[4]);
+    assert!(keys.iter().zip(&before).all(|(key, id)| ring.get_node(key) == *id));
+    ring.remove_node(&ids[0]);
+    assert_eq!(ring.ids(), &[&ids[1], &ids[2], &ids[3]]);
+}
+
+#[test]
+fn test_weighted_ring() {
+    let ids = ["alpha", "beta", "gamma", "delta"];
+    let keys: Vec<u64> = (0..60_000).collect();
+    let mut ring = WeightedRing::new(vec![(&ids[0], 1), (&ids[1], 2), (&ids[2], 3)]);
+
+    let mut counts = [0u64; 4];
+    let before: Vec<&&str> = keys.iter().map(|key| ring.get_node(key)).collect();
+    for id in &before {
+        counts[ids.iter().position(|other| other == *id).unwrap()] += 1;
+    }
+    let ratio = counts[2] as f64 / counts[0] as f64;
+    assert!(ratio > 2.7 && ratio < 3.3);
+    assert!((counts[1] as f64 / 20_000.0 - 1.0).abs() < 0.05);
+
+    ring.push(&ids[3], 2);
+    assert_eq!(ring.len(), 4);
+    assert!(keys
+        .iter()
+        .zip(&before)
+        .all(|(key, id)| ring.get_node(key) == *id || ring.get_node(key) == &ids[3]));
+
+    assert_eq!(ring.pop(), Some((&ids[3], 2)));
+    assert!(keys.iter().zip(&before).all(|(key, id)| ring.get_node(key) == *id));
+}
+
+#[test]
+#[should_panic]
+fn test_weighted_ring_total_weight_overflow() {
+    let ids = [0, 1];
+    WeightedRing::new(vec![(&ids[0], u32::MAX), (&ids[1], 1)]);
+}
@@ -1,9 +1,333 @@
+//! Implementations of hash ring algorithms.
+//!
+//! The crate uses the standard library by default. Without the default `std` feature and with the
+//! `alloc` feature, the crate is `no_std` and only provides `anchor`, `consistent`, `jump`,
+//! `rendezvous` and `util`. Their rings default to `util::SipState` instead of `RandomState`, and
+//! `rendezvous::SkeletonRing` is not available. The other modules need the floating point
+//! functions, random number generators or synchronization of the standard library.
+//!
+//! The `memory_footprint` methods of the rings return estimates of the bytes they use, which are
+//! meant for comparing rings rather than exact accounting. They count the ring and the entries of
+//! its collections, but not the bookkeeping overhead of the collections themselves, such as the
+//! internal nodes of a `BTreeMap` or the unused buckets of a `HashMap`.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "alloc")))]
+compile_error!("Either the `std` or the `alloc` feature must be enabled.");
+
+extern crate alloc;
+
+mod collections;
+#[cfg(feature = "std")]
+pub mod analysis;
+pub mod anchor;
+#[cfg(feature = "std")]
+pub mod blend;
+#[cfg(feature = "std")]
+pub mod cache;
 pub mod consistent;
 pub mod jump;
+#[cfg(feature = "std")]
 pub mod carp;
+#[cfg(feature = "std")]
+pub mod concurrent;
+#[cfg(feature = "std")]
 pub mod generator;
+#[cfg(all(feature = "std", feature = "fast-hash"))]
+pub mod hashers;
+#[cfg(feature = "std")]
 pub mod maglev;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "std")]
 pub mod mpc;
 pub mod rendezvous;
+#[cfg(feature = "std")]
 pub mod weighted_rendezvous;
 pub mod util;
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::Hash;
+
+/// An error returned by the fallible lookups of a hashing ring.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashRingError {
+    /// The ring does not contain any nodes.
+    EmptyRing,
+}
+
+impl fmt::Display for HashRingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HashRingError::EmptyRing => write!(f, "empty ring"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HashRingError {}
+
+/// A hashing ring that maps points of type `U` to nodes of type `T`.
+///
+/// The trait is implemented by the rings that support arbitrary node ids so that code can be
+/// written generically over the different hashing algorithms. Jump hashing only returns node
+/// indices, so `jump::NamedRing`, `jump::LabeledRing` or `jump::WeightedRing` implement the trait
+/// for it.
+pub trait HashRing<'a, T, U> {
+    /// Returns the node associated with a point.
+    fn get_node(&self, point: &U) -> &'a T;
+
+    /// Returns the node associated with a point, or `HashRingError::EmptyRing` if the ring is
+    /// empty. Unlike `get_node`, this never panics.
+    fn try_get_node(&self, point: &U) -> Result<&'a T, HashRingError> {
+        if self.is_empty() {
+            Err(HashRingError::EmptyRing)
+        } else {
+            Ok(self.get_node(point))
+        }
+    }
+
+    /// Returns the node associated with a point that is routed by the key `key_fn` extracts
+    /// from it.
+    ///
+    /// This allows points that do not implement `Hash` to be routed without a wrapper type.
+    fn get_node_by<V, F>(&self, point: &V, key_fn: F) -> &'a T
+        where
+            Self: Sized,
+            F: Fn(&V) -> U,
+    {
+        self.get_node(&key_fn(point))
+    }
+
+    /// Returns the ids of the nodes in the ring, in no particular order.
+    fn node_ids(&self) -> Vec<&'a T>;
+
+    /// Returns the number of nodes in the ring.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the ring is empty.
+    fn is_empty(&self) -> bool;
+}
+
+/// A hashing ring that routes keys to nodes, where the type of the node is an associated type.
+///
+/// Unlike `HashRing`, the trait is implemented by every ring, including rings that return node
+/// indices instead of node ids, such as `jump::Ring`. `Labeled` adapts a router that returns
+/// indices into a router that returns labels.
+pub trait Router {
+    /// The node a key is routed to.
+    type Output;
+
+    /// Returns the node associated with a key.
+    fn route<K>(&self, key: &K) -> Self::Output
+        where
+            K: Hash;
+}
+
+/// A router that maps the node indices returned by another router to labels.
+pub struct Labeled<'a, T, R> {
+    labels: &'a [T],
+    router: R,
+}
+
+impl<'a, T, R> Labeled<'a, T, R> {
+    /// Constructs a new `Labeled<T, R>` that maps the index returned by `router` to the label at
+    /// that index. `router` must have exactly one node for each label.
+    pub fn new(labels: &'a [T], router: R) -> Self {
+        Self { labels, router }
+    }
+}
+
+impl<'a, T, R> Router for Labeled<'a, T, R>
+    where
+        R: Router<Output = u32>,
+{
+    type Output = &'a T;
+
+    fn route<K>(&self, key: &K) -> &'a T
+        where
+            K: Hash,
+    {
+        &self.labels[self.router.route(key) as usize]
+    }
+}
+
+/// A hashing ring that supports removing nodes.
+pub trait DynamicHashRing<'a, T, U>: HashRing<'a, T, U> {
+    /// Removes a node from the ring.
+    fn remove_node(&mut self, id: &T);
+}
+
+/// A hashing ring whose lookups return the node as a `Cow`, so that rings that borrow their nodes
+/// and rings that own them can be used through the same interface.
+///
+/// `consistent::Ring`, `rendezvous::Ring` and `rendezvous::OwnedRing` return `Cow::Borrowed`,
+/// which costs the same as their `get_node`. `consistent::OwnedRing<T>` shares each node between
+/// its replicas with an `Arc<T>`, so it implements `CowHashRing<Arc<T>, U>` and returns
+/// `Cow::Owned` with a clone of the `Arc`. This only increments a reference count, and the node
+/// can be kept after the ring is modified or dropped by calling `Cow::into_owned`.
+pub trait CowHashRing<T, U>
+    where
+        T: Clone,
+{
+    /// Returns the node associated with a point.
+    fn get_node_cow(&self, point: &U) -> Cow<'_, T>;
+}
+
+#[test]
+fn test_get_node_cow() {
+    fn get_node_cow<'r, R, T>(ring: &'r R, point: &u64) -> Cow<'r, T>
+        where
+            R: CowHashRing<T, u64>,
+            T: Clone,
+    {
+        ring.get_node_cow(point)
+    }
+
+    let nodes = [0, 1, 2];
+    let hash_builder = util::DefaultState::default();
+    let mut borrowed = consistent::Ring::with_hasher(hash_builder.clone());
+    let mut owned = consistent::OwnedRing::with_hasher(hash_builder);
+    for node in &nodes {
+        borrowed.insert_node(node, 10);
+        owned.insert_node(*node, 10);
+    }
+
+    for point in 0..100 {
+        let expected = borrowed.get_node(&point);
+        let node = get_node_cow(&borrowed, &point);
+        assert!(matches!(node, Cow::Borrowed(_)));
+        assert_eq!(*node, *expected);
+        let node = get_node_cow(&owned, &point);
+        assert!(matches!(node, Cow::Owned(_)));
+        assert_eq!(**node, *expected);
+    }
+
+    let node = owned.get_node_cow(&0).into_owned();
+    owned.remove_node(&node);
+    assert_eq!(*node, *borrowed.get_node(&0));
+
+    let mut rendezvous = rendezvous::OwnedRing::new();
+    rendezvous.insert_node(0, 1);
+    assert!(matches!(get_node_cow(&rendezvous, &0), Cow::Borrowed(&0)));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_router() {
+    fn route_all<R>(router: &R, keys: &[u64]) -> Vec<R::Output>
+        where
+            R: Router,
+    {
+        keys.iter().map(|key| router.route(key)).collect()
+    }
+
+    let nodes: Vec<u64> = (0..10).collect();
+    let keys: Vec<u64> = (0..100).collect();
+    let mut ring = rendezvous::Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 1);
+    }
+    let hash_builder = std::collections::hash_map::RandomState::new();
+    let jump_ring = jump::Ring::with_hasher(hash_builder.clone(), 10);
+    let labeled = Labeled::new(&nodes, jump::Ring::with_hasher(hash_builder, 10));
+
+    let expected: Vec<&u64> = keys.iter().map(|key| ring.get_node(key)).collect();
+    assert_eq!(route_all(&ring, &keys), expected);
+    let expected: Vec<&u64> = route_all(&jump_ring, &keys)
+        .into_iter()
+        .map(|index| &nodes[index as usize])
+        .collect();
+    assert_eq!(route_all(&labeled, &keys), expected);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_try_get_node() {
+    fn assert_empty<'a, R>(ring: &R)
+        where
+            R: HashRing<'a, u64, u64>,
+    {
+        assert_eq!(ring.try_get_node(&0), Err(HashRingError::EmptyRing));
+    }
+
+    let mut consistent_ring: consistent::Ring<u64> = consistent::Ring::new();
+    let mut mpc_ring: mpc::Ring<u64> = mpc::Ring::new(2);
+    let mut rendezvous_ring: rendezvous::Ring<u64> = rendezvous::Ring::new();
+    let mut weighted_rendezvous_ring: weighted_rendezvous::Ring<u64> =
+        weighted_rendezvous::Ring::new();
+    let labels: [u64; 0] = [];
+    assert_empty(&consistent_ring);
+    assert_empty(&mpc_ring);
+    assert_empty(&rendezvous_ring);
+    assert_empty(&weighted_rendezvous_ring);
+    assert_empty(&jump::LabeledRing::new(&labels));
+    assert_empty(&jump::NamedRing::<u64>::new(Vec::new()));
+    assert_empty(&jump::WeightedRing::<u64>::new(Vec::new()));
+    assert_empty(&cache::CachedRing::new(rendezvous::Ring::<u64>::new(), 1));
+    assert_empty(&concurrent::ConcurrentRing::new(rendezvous::Ring::<u64>::new()));
+
+    // These rings are not empty, but none of their nodes have replicas.
+    let node = 0;
+    let mut ring32 = consistent::Ring32::new();
+    ring32.insert_node(&node, 0);
+    assert_empty(&ring32);
+    let mut skeleton_ring = rendezvous::SkeletonRing::new(2);
+    skeleton_ring.insert_node(&node, 0);
+    assert_empty(&skeleton_ring);
+    assert_empty(&concurrent::ConcurrentRing::new(skeleton_ring));
+
+    consistent_ring.insert_node(&node, 1);
+    mpc_ring.insert_node(&node);
+    rendezvous_ring.insert_node(&node, 1);
+    weighted_rendezvous_ring.insert_node(&node, 1.0);
+    assert_eq!(HashRing::try_get_node(&consistent_ring, &0), Ok(&node));
+    assert_eq!(HashRing::try_get_node(&mpc_ring, &0), Ok(&node));
+    assert_eq!(HashRing::try_get_node(&rendezvous_ring, &0), Ok(&node));
+    assert_eq!(HashRing::try_get_node(&weighted_rendezvous_ring, &0), Ok(&node));
+    let maglev_ring = maglev::Ring::new(vec![&node]);
+    assert_eq!(HashRing::try_get_node(&maglev_ring, &0), Ok(&node));
+    ring32.insert_node(&node, 1);
+    assert_eq!(HashRing::try_get_node(&ring32, &0), Ok(&node));
+    let named_ring = jump::NamedRing::new(vec![&node]);
+    assert_eq!(HashRing::try_get_node(&named_ring, &0), Ok(&node));
+    let weighted_ring = jump::WeightedRing::new(vec![(&node, 1)]);
+    assert_eq!(HashRing::try_get_node(&weighted_ring, &0), Ok(&node));
+    let concurrent_ring = concurrent::ConcurrentRing::new(rendezvous_ring);
+    assert_eq!(HashRing::try_get_node(&concurrent_ring, &0), Ok(&node));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_dyn_hash_ring() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut consistent_ring = consistent::Ring::new();
+    let mut mpc_ring = mpc::Ring::new(21);
+    let mut rendezvous_ring = rendezvous::Ring::new();
+    let mut weighted_rendezvous_ring = weighted_rendezvous::Ring::new();
+    for node in &nodes {
+        consistent_ring.insert_node(node, 10);
+        mpc_ring.insert_node(node);
+        rendezvous_ring.insert_node(node, 1);
+        weighted_rendezvous_ring.insert_node(node, 1.0);
+    }
+    let carp_ring = carp::Ring::new(nodes.iter().map(|node| carp::Node::new(node, 1.0)).collect());
+
+    let rings: Vec<Box<dyn HashRing<u64, u64>>> = vec![
+        Box::new(consistent_ring),
+        Box::new(carp_ring),
+        Box::new(mpc_ring),
+        Box::new(rendezvous_ring),
+        Box::new(weighted_rendezvous_ring),
+        Box::new(jump::NamedRing::new(nodes.iter().collect())),
+        Box::new(maglev::Ring::new(nodes.iter().collect())),
+    ];
+    for ring in &rings {
+        assert_eq!(ring.len(), nodes.len());
+        for key in 0..100 {
+            assert!(nodes.contains(ring.get_node(&key)));
+        }
+    }
+}
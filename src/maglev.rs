@@ -1,8 +1,35 @@
+use crate::util;
+use crate::{HashRing, Router};
 use primal::Sieve;
 use rand::Rng;
 use siphasher::sip::SipHasher;
 use std::hash::{Hash, Hasher};
+use std::cmp;
+use std::error::Error;
+use std::fmt;
 use std::iter;
+use std::mem;
+
+/// An error returned by the fallible constructors of a maglev `Ring`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaglevError {
+    /// The ring was constructed without any nodes.
+    EmptyRing,
+    /// No prime capacity could be found for the lookup table, because the capacity hint is too
+    /// large.
+    NoPrime,
+}
+
+impl fmt::Display for MaglevError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MaglevError::EmptyRing => write!(f, "empty ring"),
+            MaglevError::NoPrime => write!(f, "no prime capacity for the lookup table"),
+        }
+    }
+}
+
+impl Error for MaglevError {}
 
 /// A hashing ring implemented using maglev hashing.
 ///
@@ -10,20 +37,28 @@ use std::iter;
 /// generating random permutations.
 pub struct Ring<'a, T> {
     nodes: Vec<&'a T>,
+    weights: Vec<u32>,
+    permutations: Vec<(usize, usize)>,
     lookup: Vec<usize>,
-    hasher: SipHasher,
+    hashers: [SipHasher; 2],
 }
 
 impl<'a, T> Ring<'a, T> {
     fn get_hashers() -> [SipHasher; 2] {
         let mut rng = rand::thread_rng();
-        [
-            SipHasher::new_with_keys(rng.gen::<u64>(), rng.gen::<u64>()),
-            SipHasher::new_with_keys(rng.gen::<u64>(), rng.gen::<u64>()),
-        ]
+        util::get_keyed_hashers((rng.gen::<u64>(), rng.gen::<u64>()))
+    }
+
+    fn get_seeded_hashers(seed: u64) -> [SipHasher; 2] {
+        let key = |index: u64| util::mix_u64(seed.wrapping_mul(2).wrapping_add(index));
+        util::get_keyed_hashers((key(0), key(1)))
     }
 
     /// Constructs a new `Ring<T>` with a specified list of nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nodes` is empty. Use `try_new` to handle an empty list of nodes.
     pub fn new(nodes: Vec<&'a T>) -> Self
         where
             T: Hash,
@@ -33,6 +68,39 @@ impl<'a, T> Ring<'a, T> {
         Ring::with_capacity_hint(nodes, capacity_hint)
     }
 
+    /// Constructs a new `Ring<T>` with a specified list of nodes, or returns an error if `nodes`
+    /// is empty or if no prime capacity can be found for the lookup table.
+    pub fn try_new(nodes: Vec<&'a T>) -> Result<Self, MaglevError>
+        where
+            T: Hash,
+    {
+        let capacity_hint = nodes.len().checked_mul(100).ok_or(MaglevError::NoPrime)?;
+        Self::try_with_hashers(
+            Self::get_hashers(),
+            nodes.into_iter().map(|node| (node, 1)).collect(),
+            capacity_hint,
+        )
+    }
+
+    /// Constructs a new `Ring<T>` with a specified list of nodes whose hashers are seeded with
+    /// `keys`.
+    ///
+    /// The hashers of a ring constructed by `new` are seeded randomly, so rings with the same nodes
+    /// map keys to different nodes. Rings with the same nodes and keys have the same lookup table
+    /// and map every key to the same node, even in different processes.
+    pub fn with_seed(nodes: Vec<&'a T>, keys: (u64, u64)) -> Self
+        where
+            T: Hash,
+    {
+        assert!(!nodes.is_empty());
+        let capacity_hint = nodes.len() * 100;
+        Self::with_hashers(
+            util::get_keyed_hashers(keys),
+            nodes.into_iter().map(|node| (node, 1)).collect(),
+            capacity_hint,
+        )
+    }
+
     /// Constructs a new `Ring<T>` with a specified list of nodes and a capacity hint. The actual
     /// capacity of the ring will always be the next prime greater than or equal to
     /// `capacity_hint`. If nodes are removed and the ring is regenerated, the ring should be
@@ -41,13 +109,160 @@ impl<'a, T> Ring<'a, T> {
         where
             T: Hash,
     {
-        let hashers = Self::get_hashers();
-        let lookup = Self::populate(&hashers, &nodes, capacity_hint);
-        Self {
+        Self::with_weights_and_capacity_hint(
+            nodes.into_iter().map(|node| (node, 1)).collect(),
+            capacity_hint,
+        )
+    }
+
+    /// Constructs a new `Ring<T>` with a specified list of nodes and a capacity for up to
+    /// `max_nodes` nodes.
+    ///
+    /// The capacity is the same as the capacity of a ring constructed by `new` with `max_nodes`
+    /// nodes, so nodes can be added with `add_node` until there are `max_nodes` nodes without
+    /// changing the capacity of the ring.
+    pub fn with_capacity_for_max_nodes(nodes: Vec<&'a T>, max_nodes: usize) -> Self
+        where
+            T: Hash,
+    {
+        assert!(!nodes.is_empty());
+        assert!(nodes.len() <= max_nodes);
+        Ring::with_capacity_hint(nodes, max_nodes * 100)
+    }
+
+    /// Constructs a new `Ring<T>` with a specified list of weighted nodes.
+    ///
+    /// Nodes take turns filling the lookup table in proportion to their weights, so a node with a
+    /// weight of two will own approximately twice as many table entries as a node with a weight of
+    /// one. The capacity of the ring is at least the total weight of the nodes so that every node
+    /// is able to claim an entry.
+    pub fn with_weights(nodes: Vec<(&'a T, u32)>) -> Self
+        where
+            T: Hash,
+    {
+        assert!(!nodes.is_empty());
+        let capacity_hint = nodes.len() * 100;
+        Ring::with_weights_and_capacity_hint(nodes, capacity_hint)
+    }
+
+    /// Constructs a new `Ring<T>` with a specified list of weighted nodes and a capacity hint. The
+    /// actual capacity of the ring will always be the next prime greater than or equal to the
+    /// larger of `capacity_hint` and the total weight of the nodes after dividing out their
+    /// greatest common divisor.
+    pub fn with_weights_and_capacity_hint(nodes: Vec<(&'a T, u32)>, capacity_hint: usize) -> Self
+        where
+            T: Hash,
+    {
+        Self::with_hashers(Self::get_hashers(), nodes, capacity_hint)
+    }
+
+    /// Constructs a new `Ring<T>` with a specified list of nodes by building `trials` lookup tables
+    /// and keeping the table whose most loaded node owns the fewest entries.
+    ///
+    /// The capacity hint is `nodes.len() * multiplier`. The permutations of each trial are seeded
+    /// by `seed` and the index of the trial, so the same arguments always produce the same ring,
+    /// and the ring is never less balanced than the first trial.
+    pub fn optimized(nodes: Vec<&'a T>, multiplier: usize, trials: u64, seed: u64) -> Self
+        where
+            T: Hash,
+    {
+        assert!(!nodes.is_empty());
+        assert!(trials > 0);
+        let capacity_hint = nodes.len() * multiplier;
+        let weighted_nodes: Vec<(&'a T, u32)> = nodes.into_iter().map(|node| (node, 1)).collect();
+        (0..trials)
+            .map(|trial| {
+                Self::with_hashers(
+                    Self::get_seeded_hashers(seed.wrapping_add(trial)),
+                    weighted_nodes.clone(),
+                    capacity_hint,
+                )
+            })
+            .min_by_key(|ring| ring.get_max_count())
+            .expect("Expected positive number of trials.")
+    }
+
+    fn get_max_count(&self) -> usize {
+        Self::get_counts(&self.lookup, self.nodes.len())
+            .into_iter()
+            .max()
+            .expect("Expected non-empty ring.")
+    }
+
+    fn with_hashers(
+        hashers: [SipHasher; 2],
+        nodes: Vec<(&'a T, u32)>,
+        capacity_hint: usize,
+    ) -> Self
+        where
+            T: Hash,
+    {
+        match Self::try_with_hashers(hashers, nodes, capacity_hint) {
+            Ok(ring) => ring,
+            Err(error) => panic!("Error: {}.", error),
+        }
+    }
+
+    fn try_with_hashers(
+        hashers: [SipHasher; 2],
+        nodes: Vec<(&'a T, u32)>,
+        capacity_hint: usize,
+    ) -> Result<Self, MaglevError>
+        where
+            T: Hash,
+    {
+        if nodes.is_empty() {
+            return Err(MaglevError::EmptyRing);
+        }
+        assert!(nodes.iter().all(|node| node.1 > 0));
+        let (nodes, weights): (Vec<&'a T>, Vec<u32>) = nodes.into_iter().unzip();
+        let capacity_hint = cmp::max(capacity_hint, Self::get_total_weight(&weights));
+        let m = Self::try_get_prime(capacity_hint).ok_or(MaglevError::NoPrime)?;
+        let permutations = nodes
+            .iter()
+            .map(|node| Self::get_permutation(&hashers, node, m))
+            .collect();
+        let mut ring = Self {
             nodes,
-            lookup,
-            hasher: hashers[0],
+            weights,
+            permutations,
+            lookup: Vec::new(),
+            hashers,
+        };
+        ring.lookup = ring.populate(m);
+        Ok(ring)
+    }
+
+    fn gcd(mut a: u32, mut b: u32) -> u32 {
+        while b != 0 {
+            let r = a % b;
+            a = b;
+            b = r;
         }
+        a
+    }
+
+    // Returns the total weight of the nodes after dividing out their greatest common divisor.
+    fn get_total_weight(weights: &[u32]) -> usize {
+        let divisor = weights.iter().fold(0, |acc, weight| Self::gcd(acc, *weight));
+        weights
+            .iter()
+            .map(|weight| (*weight / divisor) as usize)
+            .sum()
+    }
+
+    fn get_prime(capacity_hint: usize) -> usize {
+        Self::try_get_prime(capacity_hint)
+            .expect("Expected a prime larger than or equal to `capacity_hint`.")
+    }
+
+    // Returns the smallest prime that is at least `capacity_hint` and at least two, so that every
+    // permutation has a non-zero skip.
+    fn try_get_prime(capacity_hint: usize) -> Option<usize> {
+        let capacity_hint = cmp::max(capacity_hint, 2);
+        Sieve::new(capacity_hint.checked_mul(2)?)
+            .primes_from(capacity_hint)
+            .next()
     }
 
     fn get_hash<U>(hasher: SipHasher, key: &U) -> usize
@@ -59,35 +274,43 @@ impl<'a, T> Ring<'a, T> {
         sip.finish() as usize
     }
 
-    fn populate(hashers: &[SipHasher; 2], nodes: &[&T], capacity_hint: usize) -> Vec<usize>
+    // The permutation of a node is described by its offset and skip, and the `i`-th entry of the
+    // permutation is `(offset + i * skip) % m`.
+    fn get_permutation(hashers: &[SipHasher; 2], node: &T, m: usize) -> (usize, usize)
         where
-            T: 'a + Hash,
+            T: Hash,
     {
-        let m = Sieve::new(capacity_hint * 2)
-            .primes_from(capacity_hint)
-            .next()
-            .expect("Expected a prime larger than or equal to `capacity_hint`.");
-        let n = nodes.len();
+        let offset = Self::get_hash(hashers[0], node) % m;
+        let skip = (Self::get_hash(hashers[1], node) % (m - 1)) + 1;
+        (offset, skip)
+    }
 
-        let permutation: Vec<Vec<usize>> = nodes
-            .iter()
-            .map(|node| {
-                let offset = Self::get_hash(hashers[0], node) % m;
-                let skip = (Self::get_hash(hashers[1], node) % (m - 1)) + 1;
-                (0..m).map(|i| (offset + i * skip) % m).collect()
-            })
-            .collect();
+    fn populate(&self, m: usize) -> Vec<usize> {
+        let n = self.nodes.len();
+        let divisor = self.weights.iter().fold(0, |acc, weight| Self::gcd(acc, *weight));
+        let weights: Vec<u32> = self.weights.iter().map(|weight| weight / divisor).collect();
 
+        let max_weight = *weights.iter().max().expect("Expected non-empty ring.");
+        let mut credit = vec![0; n];
         let mut next: Vec<usize> = iter::repeat(0).take(n).collect();
         let mut entry: Vec<usize> = iter::repeat(<usize>::max_value()).take(m).collect();
 
         let mut i = 0;
         while i < m {
             for j in 0..n {
-                let mut c = permutation[j][next[j]];
+                // A node only takes a turn once it has accumulated enough weight, so nodes with
+                // the maximum weight take a turn every round.
+                credit[j] += weights[j];
+                if credit[j] < max_weight {
+                    continue;
+                }
+                credit[j] -= max_weight;
+
+                let (offset, skip) = self.permutations[j];
+                let mut c = (offset + next[j] * skip) % m;
                 while entry[c] != <usize>::max_value() {
                     next[j] += 1;
-                    c = permutation[j][next[j]];
+                    c = (offset + next[j] * skip) % m;
                 }
                 entry[c] = j;
                 next[j] += 1;
@@ -102,6 +325,77 @@ impl<'a, T> Ring<'a, T> {
         entry
     }
 
+    // Regenerates the lookup table after the nodes changed. The capacity and hashers of the ring
+    // are reused, so only the permutations of inserted nodes are computed, unless the total weight
+    // of the nodes no longer fits in the table.
+    fn rebuild(&mut self)
+        where
+            T: Hash,
+    {
+        let mut m = self.capacity();
+        let total_weight = Self::get_total_weight(&self.weights);
+        if total_weight > m {
+            m = Self::get_prime(total_weight);
+            let hashers = self.hashers;
+            self.permutations = self
+                .nodes
+                .iter()
+                .map(|node| Self::get_permutation(&hashers, node, m))
+                .collect();
+        }
+        self.lookup = self.populate(m);
+    }
+
+    /// Inserts a node into the ring with a particular weight, or updates the weight of the node if
+    /// it is already in the ring.
+    ///
+    /// The lookup table is regenerated with the same capacity and permutations, so the table is
+    /// the same as the table of a ring that was built with the same nodes in the same order.
+    pub fn add_node(&mut self, id: &'a T, weight: u32)
+        where
+            T: Hash + Eq,
+    {
+        assert!(weight > 0);
+        if let Some(index) = self.nodes.iter().position(|node| *node == id) {
+            self.weights[index] = weight;
+        } else {
+            self.nodes.push(id);
+            self.weights.push(weight);
+            self.permutations
+                .push(Self::get_permutation(&self.hashers, id, self.capacity()));
+        }
+        self.rebuild();
+    }
+
+    /// Removes a node from the ring.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the last node of the ring is removed.
+    pub fn remove_node(&mut self, id: &T)
+        where
+            T: Hash + Eq,
+    {
+        if let Some(index) = self.nodes.iter().position(|node| *node == id) {
+            assert!(self.nodes.len() > 1, "Error: cannot remove the last node.");
+            self.nodes.remove(index);
+            self.weights.remove(index);
+            self.permutations.remove(index);
+            self.rebuild();
+        }
+    }
+
+    /// Returns an estimate of the number of bytes used by the ring: the allocated nodes, weights,
+    /// permutations and lookup table. The lookup table has one entry per slot of the capacity, so
+    /// it dominates the estimate.
+    pub fn memory_footprint(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.nodes.capacity() * mem::size_of::<&T>()
+            + self.weights.capacity() * mem::size_of::<u32>()
+            + self.permutations.capacity() * mem::size_of::<(usize, usize)>()
+            + self.lookup.capacity() * mem::size_of::<usize>()
+    }
+
     /// Returns the number of nodes in the ring.
     pub fn nodes(&self) -> usize {
         self.nodes.len()
@@ -113,12 +407,323 @@ impl<'a, T> Ring<'a, T> {
         self.lookup.len()
     }
 
+    fn get_counts(lookup: &[usize], nodes: usize) -> Vec<usize> {
+        let mut counts = vec![0; nodes];
+        for index in lookup {
+            counts[*index] += 1;
+        }
+        counts
+    }
+
+    /// Returns `true` if a node does not own any entries of the lookup table. Points are never
+    /// mapped to a starved node.
+    ///
+    /// Every turn a node takes while populating the table claims an entry, regardless of the
+    /// permutations, so a node can only be starved if the table is filled before its first turn.
+    /// Since the capacity of a ring is at least the total weight of its nodes, this indicates a
+    /// corrupted table rather than an unlucky choice of hashers. Retrying with new hashers would
+    /// not change the outcome, so the constructors do not retry.
+    pub fn has_starved_nodes(&self) -> bool {
+        Self::get_counts(&self.lookup, self.nodes.len()).contains(&0)
+    }
+
+    /// Returns the fraction of the lookup table owned by each node, in the order the nodes were
+    /// supplied.
+    pub fn distribution(&self) -> Vec<f64> {
+        Self::get_counts(&self.lookup, self.nodes.len())
+            .into_iter()
+            .map(|count| count as f64 / self.capacity() as f64)
+            .collect()
+    }
+
+    /// Returns the lookup table, which contains the index of the node that owns each entry. The
+    /// indices refer to the nodes in the order they were supplied.
+    pub fn lookup_table(&self) -> &[usize] {
+        &self.lookup
+    }
+
+    /// Returns the node that owns an entry of the lookup table.
+    pub fn node_at(&self, index: usize) -> &'a T {
+        self.nodes[self.lookup[index]]
+    }
+
+    /// Returns the number of entries of the lookup table owned by each node, in the order the nodes
+    /// were supplied.
+    pub fn slot_counts(&self) -> Vec<usize> {
+        Self::get_counts(&self.lookup, self.nodes.len())
+    }
+
+    /// Returns the node of every entry of the lookup table.
+    ///
+    /// Unlike node indices, node ids do not change when other nodes are removed, so the result can
+    /// be kept across modifications of the ring and passed to `disruption`.
+    pub fn lookup_nodes(&self) -> Vec<&'a T> {
+        self.lookup.iter().map(|index| self.nodes[*index]).collect()
+    }
+
+    /// Returns the fraction of the entries of the lookup table that are owned by a different node
+    /// than in `old`, which was returned by `lookup_nodes` before the ring was modified.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity of the ring changed since `old` was returned.
+    pub fn disruption(&self, old: &[&T]) -> f64
+        where
+            T: Eq,
+    {
+        assert_eq!(old.len(), self.capacity(), "Error: the capacity of the ring changed.");
+        let changed = self
+            .lookup
+            .iter()
+            .zip(old)
+            .filter(|(index, id)| self.nodes[**index] != **id)
+            .count();
+        changed as f64 / self.capacity() as f64
+    }
+
     /// Returns the node associated with a key.
-    pub fn get_node<U>(&self, key: &U) -> &T
+    pub fn get_node<U>(&self, key: &U) -> &'a T
         where
             U: Hash,
     {
-        let index = Self::get_hash(self.hasher, key) % self.capacity();
+        let index = Self::get_hash(self.hashers[0], key) % self.capacity();
         self.nodes[self.lookup[index]]
     }
+
+    /// Returns every node of the ring, ordered by preference for a key. The first node is the node
+    /// associated with the key.
+    ///
+    /// The ranking is found by walking the lookup table from the entry of the key and skipping
+    /// nodes that were already seen. Starved nodes are never reached and are placed last.
+    pub fn get_ranking<U>(&self, key: &U) -> Vec<&'a T>
+        where
+            U: Hash,
+    {
+        let start = Self::get_hash(self.hashers[0], key) % self.capacity();
+        let mut seen = vec![false; self.nodes.len()];
+        let mut ranking = Vec::with_capacity(self.nodes.len());
+        for i in 0..self.capacity() {
+            let index = self.lookup[(start + i) % self.capacity()];
+            if !seen[index] {
+                seen[index] = true;
+                ranking.push(self.nodes[index]);
+                if ranking.len() == self.nodes.len() {
+                    return ranking;
+                }
+            }
+        }
+        for (index, node) in self.nodes.iter().enumerate() {
+            if !seen[index] {
+                ranking.push(*node);
+            }
+        }
+        ranking
+    }
+}
+
+impl<'a, T, U> HashRing<'a, T, U> for Ring<'a, T>
+    where
+        U: Hash,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.nodes.clone()
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl<'a, T> Router for Ring<'a, T> {
+    type Output = &'a T;
+
+    fn route<K>(&self, key: &K) -> &'a T
+        where
+            K: Hash,
+    {
+        self.get_node(key)
+    }
+}
+
+#[test]
+fn test_weighted_distribution() {
+    let nodes = [0, 1, 2];
+    let ring = Ring::with_weights(vec![(&nodes[0], 1), (&nodes[1], 2), (&nodes[2], 3)]);
+    let distribution = ring.distribution();
+
+    assert!((distribution[0] - 1.0 / 6.0).abs() < 0.01);
+    assert!((distribution[1] - 2.0 / 6.0).abs() < 0.01);
+    assert!((distribution[2] - 3.0 / 6.0).abs() < 0.01);
+}
+
+#[test]
+fn test_double_weight() {
+    let nodes = [0, 1, 2];
+    let ring = Ring::with_weights(vec![(&nodes[0], 1), (&nodes[1], 1), (&nodes[2], 2)]);
+    let distribution = ring.distribution();
+
+    assert!((distribution[0] - 0.25).abs() < 0.01);
+    assert!((distribution[1] - 0.25).abs() < 0.01);
+    assert!((distribution[2] - 0.5).abs() < 0.01);
+}
+
+#[test]
+fn test_weights_exceeding_capacity() {
+    let nodes = [0, 1];
+    let ring = Ring::with_weights_and_capacity_hint(vec![(&nodes[0], 1), (&nodes[1], 1000)], 10);
+
+    assert!(ring.capacity() >= 1001);
+    assert!(ring.distribution()[0] > 0.0);
+}
+
+#[test]
+fn test_starved_nodes() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let ring = Ring::new(nodes.iter().collect());
+    let mut starved_ring = Ring::new(nodes.iter().collect());
+    starved_ring.lookup = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 0, 1];
+
+    assert!(!ring.has_starved_nodes());
+    assert!(starved_ring.has_starved_nodes());
+}
+
+#[test]
+fn test_skewed_weights_not_starved() {
+    let nodes: Vec<u64> = (0..4).collect();
+    for _ in 0..20 {
+        let ring = Ring::with_weights_and_capacity_hint(
+            vec![(&nodes[0], 1000), (&nodes[1], 1), (&nodes[2], 1), (&nodes[3], 3)],
+            1,
+        );
+
+        assert_eq!(ring.capacity(), 1009);
+        assert!(!ring.has_starved_nodes());
+        assert!(ring.slot_counts()[1..].iter().all(|count| *count > 0));
+    }
+}
+
+#[test]
+fn test_get_ranking() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let ring = Ring::new(nodes.iter().collect());
+
+    for key in 0..100 {
+        let mut ranking = ring.get_ranking(&key);
+        assert_eq!(ranking[0], ring.get_node(&key));
+        assert_eq!(ranking, ring.get_ranking(&key));
+        ranking.sort();
+        assert_eq!(ranking, nodes.iter().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_add_and_remove_node() {
+    let nodes: Vec<u64> = (0..12).collect();
+    let mut ring = Ring::with_weights(nodes[..10].iter().map(|node| (node, 1)).collect());
+    ring.remove_node(&nodes[3]);
+    ring.add_node(&nodes[10], 2);
+    ring.add_node(&nodes[11], 1);
+    ring.remove_node(&nodes[11]);
+
+    let remaining: Vec<(&u64, u32)> = nodes[..11]
+        .iter()
+        .filter(|node| **node != 3)
+        .map(|node| (node, if *node == 10 { 2 } else { 1 }))
+        .collect();
+    let expected = Ring::with_hashers(ring.hashers, remaining, ring.capacity());
+    assert_eq!(ring.capacity(), expected.capacity());
+    assert_eq!(ring.lookup, expected.lookup);
+}
+
+#[test]
+fn test_with_capacity_for_max_nodes() {
+    let nodes: Vec<u64> = (0..20).collect();
+    let mut ring = Ring::with_capacity_for_max_nodes(nodes[..2].iter().collect(), nodes.len());
+    let capacity = ring.capacity();
+
+    assert!(capacity >= 2000);
+    for node in &nodes[2..] {
+        ring.add_node(node, 1);
+        assert_eq!(ring.capacity(), capacity);
+    }
+    assert!(!ring.has_starved_nodes());
+}
+
+#[test]
+fn test_optimized() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let ring = Ring::optimized(nodes.iter().collect(), 10, 20, 7);
+    let single = Ring::with_hashers(
+        Ring::<u64>::get_seeded_hashers(7),
+        nodes.iter().map(|node| (node, 1)).collect(),
+        100,
+    );
+
+    assert_eq!(ring.capacity(), single.capacity());
+    assert!(ring.get_max_count() <= single.get_max_count());
+    assert_eq!(ring.lookup, Ring::optimized(nodes.iter().collect(), 10, 20, 7).lookup);
+}
+
+#[test]
+fn test_disruption() {
+    let nodes: Vec<u64> = (0..5).collect();
+    let mut ring = Ring::new(nodes.iter().collect());
+    let old = ring.lookup_nodes();
+    assert_eq!(ring.disruption(&old), 0.0);
+
+    let share = ring.distribution()[2];
+    ring.remove_node(&nodes[2]);
+    let disruption = ring.disruption(&old);
+    assert!(disruption >= share);
+    assert!(disruption < share * 1.5);
+
+    ring.add_node(&nodes[2], 1);
+    assert_eq!(ring.lookup_nodes().len(), old.len());
+}
+
+#[test]
+fn test_lookup_table() {
+    let nodes: Vec<u64> = (0..4).collect();
+    let ring = Ring::new(nodes.iter().collect());
+    let counts = ring.slot_counts();
+
+    assert_eq!(counts.len(), nodes.len());
+    assert_eq!(counts.iter().sum::<usize>(), ring.capacity());
+    assert_eq!(ring.lookup_table().len(), ring.capacity());
+    for (index, node_index) in ring.lookup_table().iter().enumerate() {
+        assert_eq!(ring.node_at(index), &nodes[*node_index]);
+    }
+}
+
+#[test]
+fn test_with_seed() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let ring = Ring::with_seed(nodes.iter().collect(), (1, 2));
+    let other = Ring::with_seed(nodes.iter().collect(), (1, 2));
+
+    assert_eq!(ring.lookup_table(), other.lookup_table());
+    for key in 0..1000 {
+        assert_eq!(ring.get_node(&key), other.get_node(&key));
+    }
+}
+
+#[test]
+fn test_try_new() {
+    let nodes: Vec<u64> = (0..10).collect();
+    assert_eq!(Ring::<u64>::try_new(vec![]).err(), Some(MaglevError::EmptyRing));
+    assert_eq!(Ring::<u64>::try_get_prime(usize::MAX), None);
+
+    let ring = Ring::try_new(nodes.iter().collect()).expect("Expected a ring.");
+    assert_eq!(ring.capacity(), 1009);
+    for key in 0..100 {
+        assert!(nodes.contains(ring.get_node(&key)));
+    }
 }
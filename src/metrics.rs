@@ -0,0 +1,91 @@
+//! Statistics of the loads of the nodes of a hashing ring.
+//!
+//! The statistics are computed from an occupancy map that maps each node to the number of points
+//! mapped to it, such as the one built from `analysis::simulate_distribution`. Nodes that no point
+//! is mapped to should be included with a load of zero.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+fn get_mean<T>(assignments: &HashMap<&T, u64>) -> f64 {
+    assert!(!assignments.is_empty(), "Expected non-empty occupancy map.");
+    assignments.values().sum::<u64>() as f64 / assignments.len() as f64
+}
+
+/// Returns the largest load of a node divided by the average load of the nodes. A perfectly
+/// balanced map has an imbalance of one, and so does a map in which every node has a load of zero.
+///
+/// # Panics
+///
+/// Panics if the map is empty.
+pub fn load_imbalance<T>(assignments: &HashMap<&T, u64>) -> f64
+    where
+        T: Hash + Eq,
+{
+    let mean = get_mean(assignments);
+    if mean == 0.0 {
+        return 1.0;
+    }
+    let peak = assignments.values().cloned().max().unwrap_or(0);
+    peak as f64 / mean
+}
+
+/// Returns the standard deviation of the loads of the nodes divided by the average load of the
+/// nodes. A perfectly balanced map has a coefficient of variation of zero, and so does a map in
+/// which every node has a load of zero.
+///
+/// # Panics
+///
+/// Panics if the map is empty.
+pub fn coefficient_of_variation<T>(assignments: &HashMap<&T, u64>) -> f64
+    where
+        T: Hash + Eq,
+{
+    let mean = get_mean(assignments);
+    if mean == 0.0 {
+        return 0.0;
+    }
+    let variance = assignments
+        .values()
+        .map(|load| (*load as f64 / mean - 1.0).powi(2))
+        .sum::<f64>()
+        / assignments.len() as f64;
+    variance.sqrt()
+}
+
+#[test]
+fn test_balanced() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let assignments: HashMap<&u64, u64> = nodes.iter().map(|node| (node, 100)).collect();
+
+    assert_eq!(load_imbalance(&assignments), 1.0);
+    assert_eq!(coefficient_of_variation(&assignments), 0.0);
+}
+
+#[test]
+fn test_skewed() {
+    let nodes = [0, 1, 2, 3];
+    let assignments: HashMap<&u64, u64> = nodes
+        .iter()
+        .zip(&[0, 100, 100, 200])
+        .map(|(node, load)| (node, *load))
+        .collect();
+
+    assert_eq!(load_imbalance(&assignments), 2.0);
+    assert!((coefficient_of_variation(&assignments) - 0.5f64.sqrt()).abs() < 1e-12);
+}
+
+#[test]
+fn test_zero_loads() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let assignments: HashMap<&u64, u64> = nodes.iter().map(|node| (node, 0)).collect();
+
+    assert_eq!(load_imbalance(&assignments), 1.0);
+    assert_eq!(coefficient_of_variation(&assignments), 0.0);
+}
+
+#[test]
+#[should_panic]
+fn test_empty() {
+    load_imbalance::<u64>(&HashMap::new());
+}
@@ -1,9 +1,11 @@
 use crate::util;
+use crate::{DynamicHashRing, HashRing, HashRingError, Router};
 use rand::Rng;
 use siphasher::sip::SipHasher;
 use std::collections::hash_map::RandomState;
 use std::collections::BTreeMap;
 use std::hash::{BuildHasher, Hash, Hasher};
+use std::mem;
 
 const PRIME: u64 = 0xFFFF_FFFF_FFFF_FFC5;
 
@@ -30,15 +32,25 @@ impl<'a, T> Ring<'a, T, RandomState> {
             hash_builder: Default::default(),
         }
     }
+
+    /// Constructs a new, empty `Ring<T>` with a hash count chosen for approximately `nodes_hint`
+    /// nodes.
+    ///
+    /// The hash count is `15 + ceil(3 * ln(nodes_hint))`. This keeps the peak-to-average load ratio
+    /// at roughly 1.1 to 1.2 as the number of nodes grows, and yields 29 hashes for 100 nodes.
+    pub fn with_auto_probes(nodes_hint: usize) -> Self {
+        Self::new(Self::get_auto_hash_count(nodes_hint))
+    }
 }
 
 impl<'a, T, H> Ring<'a, T, H> {
     fn get_hashers() -> [SipHasher; 2] {
         let mut rng = rand::thread_rng();
-        [
-            SipHasher::new_with_keys(rng.gen::<u64>(), rng.gen::<u64>()),
-            SipHasher::new_with_keys(rng.gen::<u64>(), rng.gen::<u64>()),
-        ]
+        util::get_keyed_hashers((rng.gen::<u64>(), rng.gen::<u64>()))
+    }
+
+    fn get_auto_hash_count(nodes_hint: usize) -> u64 {
+        15 + (3.0 * (nodes_hint.max(1) as f64).ln()).ceil() as u64
     }
 
     fn get_hashes<U>(&self, item: &U) -> [u64; 2]
@@ -87,6 +99,22 @@ impl<'a, T, H> Ring<'a, T, H> {
         }
     }
 
+    /// Constructs a new, empty `Ring<T>` that hashes `hash_count` times when a key is inserted
+    /// with a specified hash builder, and whose hashers are seeded with `keys`.
+    ///
+    /// The positions of nodes are hashed with the hash builder and keys are hashed with the
+    /// hashers. Rings with the same nodes, keys, and a deterministic hash builder such as
+    /// `util::SipState` map every key to the same node, even in different processes.
+    pub fn with_hasher_and_seed(hash_builder: H, hash_count: u64, keys: (u64, u64)) -> Self {
+        assert!(hash_count > 0);
+        Self {
+            nodes: BTreeMap::new(),
+            hash_count,
+            hashers: util::get_keyed_hashers(keys),
+            hash_builder,
+        }
+    }
+
     /// Inserts a node into the ring with a number of replicas.
     ///
     /// Increasing the number of replicas will increase the number of expected points mapped to the
@@ -110,22 +138,93 @@ impl<'a, T, H> Ring<'a, T, H> {
         self.nodes.remove(&util::gen_hash(&self.hash_builder, id));
     }
 
+    /// Removes every node from the ring. The hash builder of the ring is kept.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
     /// Returns the node associated with a point.
-    pub fn get_node<U>(&self, point: &U) -> &T
+    ///
+    /// If several probes are equally close to their next nodes, the node with the lowest position
+    /// is returned, so rings with the same nodes and hash builder agree on every point.
+    pub fn get_node<U>(&self, point: &U) -> &'a T
         where
             U: Hash,
     {
-        let hashes = self.get_hashes(point);
-        let hash = (0..self.hash_count)
+        self.try_get_node(point).expect("Error: empty ring.")
+    }
+
+    /// Returns the node associated with a point, or `HashRingError::EmptyRing` if the ring is
+    /// empty.
+    pub fn try_get_node<U>(&self, point: &U) -> Result<&'a T, HashRingError>
+        where
+            U: Hash,
+    {
+        if self.nodes.is_empty() {
+            Err(HashRingError::EmptyRing)
+        } else {
+            Ok(self.get_node_by_hashes(self.get_hashes(point), self.hash_count))
+        }
+    }
+
+    /// Returns the node associated with a point using `probes` hashes of the point instead of the
+    /// hash count of the ring. Fewer probes are faster but balance the points less evenly.
+    pub fn get_node_with_probes<U>(&self, point: &U, probes: u64) -> &'a T
+        where
+            U: Hash,
+    {
+        assert!(probes > 0);
+        if self.nodes.is_empty() {
+            panic!("Error: empty ring.");
+        }
+        self.get_node_by_hashes(self.get_hashes(point), probes)
+    }
+
+    fn get_node_by_hashes(&self, hashes: [u64; 2], probes: u64) -> &'a T
+    {
+        self.nodes[&self.get_position_by_hashes(hashes, probes)]
+    }
+
+    // Returns the position of the node that is closest to any of the probes.
+    fn get_position_by_hashes(&self, hashes: [u64; 2], probes: u64) -> u64
+    {
+        (0..probes)
             .map(|i| {
                 let hash = hashes[0].wrapping_add((i as u64).wrapping_mul(hashes[1]) % PRIME);
                 let next_hash = self.get_next_hash(hash);
                 (Self::get_distance(hash, next_hash), next_hash)
             })
-            .min()
-            .expect("Error: expected positive hash count.");
+            // Probes at the same distance from their next nodes are broken by the lowest position,
+            // so ties do not depend on the order of the probes or need `T: Ord`.
+            .min_by(|n, m| n.0.cmp(&m.0).then_with(|| n.1.cmp(&m.1)))
+            .expect("Error: expected positive hash count.")
+            .1
+    }
 
-        self.nodes[&hash.1]
+    /// Returns up to `n` distinct nodes associated with a point. The first node is the node
+    /// returned by `get_node`, and the other nodes follow it clockwise around the ring.
+    ///
+    /// All the nodes of the ring are returned if the ring has fewer than `n` nodes.
+    pub fn get_nodes<U>(&self, point: &U, n: usize) -> Vec<&'a T>
+        where
+            U: Hash,
+    {
+        if self.nodes.is_empty() {
+            return Vec::new();
+        }
+        let position = self.get_position_by_hashes(self.get_hashes(point), self.hash_count);
+        self.nodes
+            .range(position..)
+            .chain(self.nodes.range(..position))
+            .map(|entry| *entry.1)
+            .take(n)
+            .collect()
+    }
+
+    /// Returns an estimate of the number of bytes used by the ring: one position per node, since
+    /// multi-probe hashing probes the key several times instead of replicating the nodes.
+    pub fn memory_footprint(&self) -> usize {
+        mem::size_of::<Self>() + self.nodes.len() * mem::size_of::<(u64, &T)>()
     }
 
     /// Returns the number of nodes in the ring.
@@ -138,3 +237,174 @@ impl<'a, T, H> Ring<'a, T, H> {
         self.nodes.is_empty()
     }
 }
+
+impl<'a, T, U, H> HashRing<'a, T, U> for Ring<'a, T, H>
+    where
+        T: Hash,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn try_get_node(&self, point: &U) -> Result<&'a T, HashRingError> {
+        self.try_get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.nodes.values().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<'a, T, U, H> DynamicHashRing<'a, T, U> for Ring<'a, T, H>
+    where
+        T: Hash,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn remove_node(&mut self, id: &T) {
+        self.remove_node(id)
+    }
+}
+
+impl<'a, T, H> Router for Ring<'a, T, H> {
+    type Output = &'a T;
+
+    fn route<K>(&self, key: &K) -> &'a T
+        where
+            K: Hash,
+    {
+        self.get_node(key)
+    }
+}
+
+#[test]
+fn test_with_auto_probes() {
+    use std::collections::HashMap;
+
+    assert_eq!(Ring::<u64>::get_auto_hash_count(0), 15);
+    assert_eq!(Ring::<u64>::get_auto_hash_count(100), 29);
+
+    for node_count in &[10u64, 50, 100] {
+        let nodes: Vec<u64> = (0..*node_count).collect();
+        let mut ring = Ring::with_auto_probes(nodes.len());
+        for node in &nodes {
+            ring.insert_node(node);
+        }
+
+        let keys = node_count * 1000;
+        let mut counts = HashMap::new();
+        for key in 0..keys {
+            *counts.entry(ring.get_node(&key)).or_insert(0) += 1;
+        }
+        let peak = *counts.values().max().expect("Expected non-empty ring.");
+        assert!(peak as f64 / 1000.0 < 1.5);
+    }
+}
+
+#[test]
+fn test_distance_tie_break() {
+    let nodes = [2, 1];
+    let mut ring = Ring::new(2);
+    ring.nodes.insert(100, &nodes[0]);
+    ring.nodes.insert(200, &nodes[1]);
+
+    // The probes are at 90 and 190, so both are a distance of 10 from their next nodes, and the
+    // node at the lowest position wins.
+    assert_eq!(ring.get_node_by_hashes([90, 100], 2), &2);
+    assert_eq!(ring.get_node_by_hashes([190, u64::MAX - 99], 2), &2);
+
+    // Only checks that `get_node` compiles for nodes that are not `Ord`.
+    #[allow(dead_code)]
+    #[derive(Hash, PartialEq, Eq)]
+    struct Unordered;
+    fn _get_unordered<'a>(ring: &Ring<'a, Unordered>) -> &'a Unordered {
+        ring.get_node(&0)
+    }
+}
+
+#[test]
+fn test_clear() {
+    let nodes = [0, 1, 2];
+    let mut ring = Ring::new(2);
+    for node in &nodes {
+        ring.insert_node(node);
+    }
+    ring.clear();
+    assert!(ring.is_empty());
+    assert_eq!(ring.len(), 0);
+
+    ring.insert_node(&nodes[0]);
+    assert_eq!(ring.get_node(&0), &nodes[0]);
+}
+
+#[test]
+fn test_try_get_node() {
+    let node = 0;
+    let mut ring = Ring::new(2);
+    assert_eq!(ring.try_get_node(&0), Err(HashRingError::EmptyRing));
+
+    ring.insert_node(&node);
+    assert_eq!(ring.try_get_node(&0), Ok(&node));
+}
+
+#[test]
+fn test_with_hasher_and_seed() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::with_hasher_and_seed(util::SipState::new(1, 2), 21, (3, 4));
+    let mut other = Ring::with_hasher_and_seed(util::SipState::new(1, 2), 21, (3, 4));
+    for node in &nodes {
+        ring.insert_node(node);
+        other.insert_node(node);
+    }
+
+    for key in 0..1000 {
+        assert_eq!(ring.get_node(&key), other.get_node(&key));
+    }
+}
+
+#[test]
+fn test_get_node_with_probes() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new(21);
+    for node in &nodes {
+        ring.insert_node(node);
+    }
+
+    for point in 0..1000 {
+        assert_eq!(ring.get_node_with_probes(&point, ring.hash_count), ring.get_node(&point));
+        assert!(nodes.contains(ring.get_node_with_probes(&point, 1)));
+    }
+}
+
+#[test]
+fn test_get_nodes() {
+    let nodes: Vec<u64> = (0..4).collect();
+    let mut ring = Ring::new(21);
+    assert!(ring.get_nodes(&0, 3).is_empty());
+    for node in &nodes {
+        ring.insert_node(node);
+    }
+
+    for point in 0..100 {
+        let mut replicas = ring.get_nodes(&point, 3);
+        assert_eq!(replicas[0], ring.get_node(&point));
+        replicas.sort_unstable();
+        replicas.dedup();
+        assert_eq!(replicas.len(), 3);
+
+        let mut all = ring.get_nodes(&point, 10);
+        assert_eq!(all.len(), 4);
+        all.sort_unstable();
+        assert_eq!(all, nodes.iter().collect::<Vec<_>>());
+    }
+}
@@ -1,8 +1,42 @@
-use crate::util;
+use crate::util::{self, HashedPoint};
+use crate::{CowHashRing, DynamicHashRing, HashRing, HashRingError, Router};
+use crate::collections::HashMap;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use alloc::collections::BTreeSet;
+#[cfg(feature = "std")]
+use core::cmp;
+use core::hash::{BuildHasher, Hash};
+use core::mem;
+#[cfg(any(test, feature = "std"))]
+use alloc::vec;
+#[cfg(all(test, feature = "std"))]
 use std::collections::hash_map::RandomState;
-use std::collections::{HashMap, HashSet};
-use std::hash::{BuildHasher, Hash};
-use std::vec::Vec;
+
+fn gen_replica_hash<T, H>(hash_builder: &H, id: &T, index: usize) -> u64
+    where
+        T: Hash,
+        H: BuildHasher,
+{
+    util::combine_hash(
+        hash_builder,
+        util::gen_hash(hash_builder, id),
+        util::gen_hash(hash_builder, &index),
+    )
+}
+
+// Returns the score of a node for a point, which is the greatest score of its replicas, or `None`
+// if the node has no replicas.
+fn gen_score<H>(hash_builder: &H, hashes: &[u64], point_hash: u64) -> Option<u64>
+    where
+        H: BuildHasher,
+{
+    hashes
+        .iter()
+        .map(|hash| util::combine_hash(hash_builder, *hash, point_hash))
+        .max()
+}
 
 /// A hashing ring implemented using rendezvous hashing.
 ///
@@ -10,12 +44,12 @@ use std::vec::Vec;
 /// A point is mapped to the node that yields the greatest value associated with the node-point
 /// pair. By mapping the weights to `[0, 1)` using logarithms, rendezvous hashing can be modified
 /// to handle weighted nodes.
-pub struct Ring<'a, T, H = RandomState> {
+pub struct Ring<'a, T, H = util::DefaultState> {
     nodes: HashMap<&'a T, Vec<u64>>,
     hash_builder: H,
 }
 
-impl<'a, T> Ring<'a, T, RandomState> {
+impl<'a, T> Ring<'a, T, util::DefaultState> {
     /// Constructs a new, empty `Ring<T>`.
     pub fn new() -> Self
         where
@@ -33,7 +67,7 @@ impl<'a, T, H> Ring<'a, T, H> {
             H: BuildHasher,
     {
         Self {
-            nodes: HashMap::new(),
+            nodes: HashMap::default(),
             hash_builder,
         }
     }
@@ -49,17 +83,49 @@ impl<'a, T, H> Ring<'a, T, H> {
             H: BuildHasher,
     {
         let hashes = (0..replicas)
-            .map(|index| {
-                util::combine_hash(
-                    &self.hash_builder,
-                    util::gen_hash(&self.hash_builder, id),
-                    util::gen_hash(&self.hash_builder, &index),
-                )
-            })
+            .map(|index| self.get_replica_hash(id, index))
             .collect();
         self.nodes.insert(id, hashes);
     }
 
+    fn get_replica_hash(&self, id: &T, index: usize) -> u64
+        where
+            T: Hash,
+            H: BuildHasher,
+    {
+        gen_replica_hash(&self.hash_builder, id, index)
+    }
+
+    /// Changes the number of replicas of a node. If the node is not in the ring, it is inserted.
+    ///
+    /// The hash of a replica only depends on its index, so only the hashes of added replicas are
+    /// computed and the hashes of removed replicas are discarded.
+    pub fn update_replicas(&mut self, id: &'a T, replicas: usize)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        let mut hashes = self.nodes.remove(id).unwrap_or_default();
+        hashes.truncate(replicas);
+        for index in hashes.len()..replicas {
+            hashes.push(self.get_replica_hash(id, index));
+        }
+        self.nodes.insert(id, hashes);
+    }
+
+    /// Removes every node from the ring. The hash builder of the ring is kept.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
+    /// Returns `true` if the node is in the ring.
+    pub fn contains_node(&self, id: &T) -> bool
+        where
+            T: Hash + Eq,
+    {
+        self.nodes.contains_key(id)
+    }
+
     /// Removes a node and all its replicas from the ring.
     pub fn remove_node(&mut self, id: &T)
         where
@@ -69,29 +135,126 @@ impl<'a, T, H> Ring<'a, T, H> {
     }
 
     /// Returns the node associated with a point.
+    ///
+    /// If several nodes share the greatest score for the point, the node with the lowest id is
+    /// returned. This guarantees that rings with the same nodes agree on every point, regardless of
+    /// the order the nodes were inserted in.
     pub fn get_node<U>(&self, id: &U) -> &'a T
         where
             T: Hash + Ord,
             U: Hash,
             H: BuildHasher,
     {
-        let point_hash = util::gen_hash(&self.hash_builder, id);
+        self.try_get_node(id).expect("Expected non-empty ring.")
+    }
+
+    /// Returns the node associated with a point, or `HashRingError::EmptyRing` if the ring is
+    /// empty or none of its nodes have replicas.
+    pub fn try_get_node<U>(&self, id: &U) -> Result<&'a T, HashRingError>
+        where
+            T: Hash + Ord,
+            U: Hash,
+            H: BuildHasher,
+    {
+        self.find(util::gen_hash(&self.hash_builder, id)).ok_or(HashRingError::EmptyRing)
+    }
+
+    /// Returns the node associated with a point that was hashed by `util::hash_point` with the
+    /// same hash builder as the ring.
+    pub fn get_node_prehashed(&self, point: &HashedPoint) -> &'a T
+        where
+            T: Hash + Ord,
+            H: BuildHasher,
+    {
+        self.find(point.value()).expect("Expected non-empty ring.")
+    }
+
+    /// Returns the keys in `keys` that are mapped to a node, in the same order. No keys are
+    /// returned if the node is not in the ring.
+    pub fn keys_for_node<'k, U>(&self, id: &T, keys: &'k [U]) -> Vec<&'k U>
+        where
+            T: Hash + Ord,
+            U: Hash,
+            H: BuildHasher,
+    {
+        keys.iter()
+            .filter(|key| self.try_get_node(*key).is_ok_and(|node| node == id))
+            .collect()
+    }
+
+    /// Returns the nodes associated with a slice of points. The result is the same as calling
+    /// `get_node` for every point.
+    ///
+    /// The nodes and their replica hashes are collected once and reused for every point, which
+    /// avoids iterating the hash map of nodes for every point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring is empty and `points` is not empty.
+    pub fn get_node_batch<U>(&self, points: &[U]) -> Vec<&'a T>
+        where
+            T: Hash + Ord,
+            U: Hash,
+            H: BuildHasher,
+    {
+        let nodes: Vec<(&'a T, &[u64])> = self
+            .nodes
+            .iter()
+            .map(|(id, hashes)| (*id, hashes.as_slice()))
+            .collect();
+        points
+            .iter()
+            .map(|point| {
+                let point_hash = util::gen_hash(&self.hash_builder, point);
+                nodes
+                    .iter()
+                    .filter_map(|(id, hashes)| {
+                        Some((gen_score(&self.hash_builder, hashes, point_hash)?, *id))
+                    })
+                    .max_by(|n, m| n.0.cmp(&m.0).then_with(|| m.1.cmp(n.1)))
+                    .expect("Expected non-empty ring.")
+                    .1
+            })
+            .collect()
+    }
+
+    fn find(&self, point_hash: u64) -> Option<&'a T>
+        where
+            T: Ord,
+            H: BuildHasher,
+    {
+        self.get_scores(point_hash)
+            .max_by(|n, m| n.0.cmp(&m.0).then_with(|| m.1.cmp(n.1)))
+            .map(|entry| entry.1)
+    }
+
+    fn get_scores(&self, point_hash: u64) -> impl Iterator<Item = (u64, &'a T)> + '_
+        where
+            H: BuildHasher,
+    {
         self.nodes
             .iter()
-            .map(|entry| {
-                (
-                    entry
-                        .1
-                        .iter()
-                        .map(|hash| util::combine_hash(&self.hash_builder, *hash, point_hash))
-                        .max()
-                        .expect("Expected non-zero number of replicas."),
-                    entry.0,
-                )
+            .filter_map(move |entry| {
+                Some((gen_score(&self.hash_builder, entry.1, point_hash)?, *entry.0))
             })
-            .max()
-            .expect("Expected non-empty ring.")
-            .1
+    }
+
+    /// Returns up to `n` nodes associated with a point, ordered from the greatest score to the
+    /// lowest score. The first node is the node returned by `get_node`.
+    ///
+    /// All the nodes of the ring are returned if the ring has fewer than `n` nodes. Nodes with zero
+    /// replicas are never returned.
+    pub fn get_nodes<U>(&self, point: &U, n: usize) -> Vec<&'a T>
+        where
+            T: Hash + Ord,
+            U: Hash,
+            H: BuildHasher,
+    {
+        let mut scores: Vec<(u64, &'a T)> = self
+            .get_scores(util::gen_hash(&self.hash_builder, point))
+            .collect();
+        scores.sort_by(|n, m| m.0.cmp(&n.0).then_with(|| n.1.cmp(m.1)));
+        scores.into_iter().take(n).map(|entry| entry.1).collect()
     }
 
     fn get_hashes(&self, id: &T) -> Vec<u64>
@@ -101,6 +264,30 @@ impl<'a, T, H> Ring<'a, T, H> {
         self.nodes[id].clone()
     }
 
+    /// Returns a fingerprint of the nodes of the ring and their numbers of replicas.
+    ///
+    /// Rings with the same nodes and replica counts have the same fingerprint, regardless of the
+    /// order the nodes were inserted in or the hash builder of the ring.
+    pub fn fingerprint(&self) -> u64
+        where
+            T: Hash,
+    {
+        util::gen_fingerprint(self.nodes.iter().map(|(id, hashes)| (id, hashes.len())))
+    }
+
+    /// Returns an estimate of the number of bytes used by the ring: every node with the allocated
+    /// hashes of its replicas.
+    pub fn memory_footprint(&self) -> usize {
+        mem::size_of::<Self>()
+            + self
+                .nodes
+                .values()
+                .map(|hashes| {
+                    mem::size_of::<(&T, Vec<u64>)>() + hashes.capacity() * mem::size_of::<u64>()
+                })
+                .sum::<usize>()
+    }
+
     /// Returns the number of nodes in the ring.
     pub fn len(&self) -> usize
         where
@@ -126,4 +313,709 @@ impl<'a, T, H> Default for Ring<'a, T, H>
     fn default() -> Self {
         Self::with_hasher(Default::default())
     }
-}
\ No newline at end of file
+}
+
+impl<'a, T, U, H> HashRing<'a, T, U> for Ring<'a, T, H>
+    where
+        T: Hash + Ord,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn try_get_node(&self, point: &U) -> Result<&'a T, HashRingError> {
+        self.try_get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.nodes.keys().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<'a, T, U, H> DynamicHashRing<'a, T, U> for Ring<'a, T, H>
+    where
+        T: Hash + Ord,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn remove_node(&mut self, id: &T) {
+        self.remove_node(id)
+    }
+}
+
+impl<'a, T, U, H> CowHashRing<T, U> for Ring<'a, T, H>
+    where
+        T: Hash + Ord + Clone,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn get_node_cow(&self, point: &U) -> Cow<'_, T> {
+        Cow::Borrowed(self.get_node(point))
+    }
+}
+
+impl<'a, T, H> Router for Ring<'a, T, H>
+    where
+        T: Hash + Ord,
+        H: BuildHasher,
+{
+    type Output = &'a T;
+
+    fn route<K>(&self, key: &K) -> &'a T
+        where
+            K: Hash,
+    {
+        self.get_node(key)
+    }
+}
+
+/// A hashing ring implemented using rendezvous hashing that owns its nodes.
+///
+/// Unlike `Ring`, the ring does not borrow the ids of its nodes, so it can be moved or stored
+/// without the storage of the ids. Lookups return references to the ids stored in the ring. A
+/// point is mapped to the same node as a `Ring` with the same nodes and hash builder.
+pub struct OwnedRing<T, H = util::DefaultState> {
+    nodes: HashMap<T, Vec<u64>>,
+    hash_builder: H,
+}
+
+impl<T> OwnedRing<T, util::DefaultState> {
+    /// Constructs a new, empty `OwnedRing<T>`.
+    pub fn new() -> Self
+        where
+            T: Hash + Eq,
+    {
+        Self::default()
+    }
+}
+
+impl<T, H> OwnedRing<T, H> {
+    /// Constructs a new, empty `OwnedRing<T>` with a specified hash builder.
+    pub fn with_hasher(hash_builder: H) -> Self
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        Self {
+            nodes: HashMap::default(),
+            hash_builder,
+        }
+    }
+
+    /// Inserts a node into the ring with a number of replicas. If the node is already in the ring,
+    /// its replicas are replaced.
+    pub fn insert_node(&mut self, id: T, replicas: usize)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        let hashes = (0..replicas)
+            .map(|index| gen_replica_hash(&self.hash_builder, &id, index))
+            .collect();
+        self.nodes.insert(id, hashes);
+    }
+
+    /// Removes a node and all its replicas from the ring, and returns the id of the node, or
+    /// `None` if the node is not in the ring.
+    pub fn remove_node(&mut self, id: &T) -> Option<T>
+        where
+            T: Hash + Eq,
+    {
+        self.nodes.remove_entry(id).map(|entry| entry.0)
+    }
+
+    /// Returns the node associated with a point.
+    ///
+    /// If several nodes share the greatest score for the point, the node with the lowest id is
+    /// returned.
+    pub fn get_node<U>(&self, point: &U) -> &T
+        where
+            T: Ord,
+            U: Hash,
+            H: BuildHasher,
+    {
+        self.try_get_node(point).expect("Expected non-empty ring.")
+    }
+
+    /// Returns the node associated with a point, or `HashRingError::EmptyRing` if the ring is
+    /// empty or none of its nodes have replicas.
+    pub fn try_get_node<U>(&self, point: &U) -> Result<&T, HashRingError>
+        where
+            T: Ord,
+            U: Hash,
+            H: BuildHasher,
+    {
+        let point_hash = util::gen_hash(&self.hash_builder, point);
+        self.nodes
+            .iter()
+            .filter_map(|entry| {
+                Some((gen_score(&self.hash_builder, entry.1, point_hash)?, entry.0))
+            })
+            .max_by(|n, m| n.0.cmp(&m.0).then_with(|| m.1.cmp(n.1)))
+            .map(|entry| entry.1)
+            .ok_or(HashRingError::EmptyRing)
+    }
+
+    /// Returns the ids of the nodes in the ring, in no particular order.
+    pub fn node_ids(&self) -> impl Iterator<Item = &T> {
+        self.nodes.keys()
+    }
+
+    /// Returns the number of nodes in the ring.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the ring is empty.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl<T, H> Default for OwnedRing<T, H>
+    where
+        T: Hash + Eq,
+        H: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(Default::default())
+    }
+}
+
+impl<T, U, H> CowHashRing<T, U> for OwnedRing<T, H>
+    where
+        T: Hash + Ord + Clone,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn get_node_cow(&self, point: &U) -> Cow<'_, T> {
+        Cow::Borrowed(self.get_node(point))
+    }
+}
+
+/// A hashing ring implemented using skeleton-based rendezvous hashing.
+///
+/// The nodes are the leaves of a virtual tree in which every cluster has up to `fan_out`
+/// children. A point descends from the root by choosing the child with the greatest score at each
+/// level, which is weighted rendezvous hashing over the children, so a lookup computes
+/// `O(fan_out * log n)` scores rather than one score for every node. If the fan-out is at least
+/// the number of nodes, the tree has a single level and points are mapped to the same nodes as a
+/// `Ring` with the same nodes and hash builder.
+///
+/// Clusters are weighted by the total number of replicas of their nodes, so every node receives
+/// approximately the same share of the points as in a `Ring`. A removed node leaves an empty slot
+/// that the next inserted node takes, so the other nodes keep their places in the tree. Changing
+/// the weight of a cluster only moves points between it and its siblings, so inserting or
+/// removing a node moves its own points and, unlike a flat ring, a small fraction of the points
+/// of the other nodes in its clusters.
+///
+/// Scoring clusters needs the floating point logarithm of the standard library, so the ring is
+/// only available with the `std` feature.
+#[cfg(feature = "std")]
+pub struct SkeletonRing<'a, T, H = util::DefaultState> {
+    // The leaves of the tree. A removed node leaves `None` in its slot, unless it was the last.
+    slots: Vec<Option<(&'a T, Vec<u64>)>>,
+    // The total number of replicas in the first `i` slots, for every `i` up to `slots.len()`.
+    replica_sums: Vec<usize>,
+    indices: HashMap<&'a T, usize>,
+    free: BTreeSet<usize>,
+    fan_out: usize,
+    hash_builder: H,
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> SkeletonRing<'a, T, util::DefaultState> {
+    /// Constructs a new, empty `SkeletonRing<T>` with a specified fan-out.
+    pub fn new(fan_out: usize) -> Self
+        where
+            T: Hash + Eq,
+    {
+        Self::with_hasher(Default::default(), fan_out)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T, H> SkeletonRing<'a, T, H> {
+    /// Constructs a new, empty `SkeletonRing<T>` with a specified hash builder and fan-out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fan_out` is less than two.
+    pub fn with_hasher(hash_builder: H, fan_out: usize) -> Self
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        assert!(fan_out >= 2, "Error: fan-out must be at least two.");
+        Self {
+            slots: Vec::new(),
+            replica_sums: vec![0],
+            indices: HashMap::default(),
+            free: BTreeSet::new(),
+            fan_out,
+            hash_builder,
+        }
+    }
+
+    /// Inserts a node into the ring with a number of replicas.
+    ///
+    /// If the node is already in the ring, its number of replicas is updated. A node with zero
+    /// replicas is not mapped to any points.
+    pub fn insert_node(&mut self, id: &'a T, replicas: usize)
+        where
+            T: Hash + Eq,
+            H: BuildHasher,
+    {
+        let hashes = (0..replicas)
+            .map(|index| gen_replica_hash(&self.hash_builder, id, index))
+            .collect();
+        let index = match self.indices.get(id) {
+            Some(index) => *index,
+            None => {
+                let index = self.free.pop_first().unwrap_or(self.slots.len());
+                if index == self.slots.len() {
+                    self.slots.push(None);
+                }
+                self.indices.insert(id, index);
+                index
+            }
+        };
+        self.slots[index] = Some((id, hashes));
+        self.update_replica_sums(index);
+    }
+
+    /// Removes a node and all its replicas from the ring.
+    pub fn remove_node(&mut self, id: &T)
+        where
+            T: Hash + Eq,
+    {
+        if let Some(index) = self.indices.remove(id) {
+            self.slots[index] = None;
+            self.free.insert(index);
+            while let Some(None) = self.slots.last() {
+                self.slots.pop();
+                self.free.remove(&self.slots.len());
+            }
+            self.update_replica_sums(cmp::min(index, self.slots.len()));
+        }
+    }
+
+    // Recomputes the replica sums of the slots from `index` onwards.
+    fn update_replica_sums(&mut self, index: usize) {
+        self.replica_sums.truncate(index + 1);
+        for slot in &self.slots[index..] {
+            let replicas = slot.as_ref().map_or(0, |entry| entry.1.len());
+            self.replica_sums.push(self.replica_sums[self.replica_sums.len() - 1] + replicas);
+        }
+    }
+
+    /// Returns the node associated with a point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring has no replicas.
+    pub fn get_node<U>(&self, point: &U) -> &'a T
+        where
+            T: Ord,
+            U: Hash,
+            H: BuildHasher,
+    {
+        self.try_get_node(point).expect("Expected non-empty ring.")
+    }
+
+    /// Returns the node associated with a point, or `HashRingError::EmptyRing` if the ring has no
+    /// replicas.
+    pub fn try_get_node<U>(&self, point: &U) -> Result<&'a T, HashRingError>
+        where
+            T: Ord,
+            U: Hash,
+            H: BuildHasher,
+    {
+        self.find(util::gen_hash(&self.hash_builder, point))
+            .map(|entry| entry.0)
+            .ok_or(HashRingError::EmptyRing)
+    }
+
+    // Clusters are weighted by their number of replicas, using the logarithm of the weighted
+    // rendezvous score `-replicas / ln(ratio)`, where `ratio` is the hash mapped to `[0, 1]`.
+    fn get_cluster_score(replicas: usize, hash: u64) -> f64 {
+        let ratio = hash as f64 / u64::MAX as f64;
+        (replicas as f64).ln() - (-ratio.ln()).ln()
+    }
+
+    // Returns the node associated with a point and the number of scores that were computed, or
+    // `None` if the ring has no replicas.
+    fn find(&self, point_hash: u64) -> Option<(&'a T, usize)>
+        where
+            T: Ord,
+            H: BuildHasher,
+    {
+        let len = self.slots.len();
+        if self.replica_sums[len] == 0 {
+            return None;
+        }
+        let mut size = 1;
+        while size * self.fan_out < len {
+            size *= self.fan_out;
+        }
+
+        let mut start = 0;
+        let mut scores = 0;
+        while size > 1 {
+            // Clusters without replicas are skipped, and ties are broken by the first cluster.
+            start = (start..len)
+                .step_by(size)
+                .take(self.fan_out)
+                .filter_map(|child| {
+                    let end = cmp::min(child + size, len);
+                    let replicas = self.replica_sums[end] - self.replica_sums[child];
+                    if replicas == 0 {
+                        return None;
+                    }
+                    let cluster_hash = util::gen_hash(&self.hash_builder, &(size, child));
+                    let hash = util::combine_hash(&self.hash_builder, cluster_hash, point_hash);
+                    scores += 1;
+                    Some((Self::get_cluster_score(replicas, hash), child))
+                })
+                .max_by(|n, m| n.0.total_cmp(&m.0).then_with(|| m.1.cmp(&n.1)))
+                .expect("Expected a cluster with replicas.")
+                .1;
+            size /= self.fan_out;
+        }
+
+        let leaves = &self.slots[start..cmp::min(start + self.fan_out, len)];
+        let node = leaves
+            .iter()
+            .flatten()
+            .filter_map(|entry| {
+                scores += 1;
+                Some((gen_score(&self.hash_builder, &entry.1, point_hash)?, entry.0))
+            })
+            .max_by(|n, m| n.0.cmp(&m.0).then_with(|| m.1.cmp(n.1)))
+            .expect("Expected a node with replicas.")
+            .1;
+        Some((node, scores))
+    }
+
+    /// Returns the number of nodes in the ring.
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns `true` if the ring is empty.
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T, U, H> HashRing<'a, T, U> for SkeletonRing<'a, T, H>
+    where
+        T: Hash + Ord,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn try_get_node(&self, point: &U) -> Result<&'a T, HashRingError> {
+        self.try_get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.indices.keys().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T, U, H> DynamicHashRing<'a, T, U> for SkeletonRing<'a, T, H>
+    where
+        T: Hash + Ord,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn remove_node(&mut self, id: &T) {
+        self.remove_node(id)
+    }
+}
+
+#[test]
+fn test_tie_break() {
+    let nodes = [3, 1, 2];
+    let mut ring = Ring::with_hasher(util::ConstantState);
+    for node in &nodes {
+        ring.insert_node(node, 2);
+    }
+
+    for point in 0..100 {
+        assert_eq!(ring.get_node(&point), &1);
+    }
+}
+
+#[test]
+fn test_zero_replicas() {
+    let nodes = [0, 1];
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 0);
+    assert_eq!(ring.try_get_node(&0), Err(HashRingError::EmptyRing));
+
+    ring.insert_node(&nodes[1], 1);
+    for point in 0..100 {
+        assert_eq!(ring.get_node(&point), &nodes[1]);
+        assert_eq!(ring.get_nodes(&point, 2), vec![&nodes[1]]);
+    }
+    assert_eq!(ring.get_node_batch(&[0, 1]), vec![&nodes[1], &nodes[1]]);
+}
+
+#[test]
+fn test_update_replicas() {
+    let nodes = [0, 1];
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 10);
+    let hashes = ring.get_hashes(&nodes[0]);
+
+    ring.update_replicas(&nodes[0], 4);
+    assert_eq!(ring.get_hashes(&nodes[0]), &hashes[..4]);
+    ring.update_replicas(&nodes[0], 10);
+    assert_eq!(ring.get_hashes(&nodes[0]), hashes);
+
+    ring.update_replicas(&nodes[1], 3);
+    assert_eq!(ring.len(), 2);
+    assert_eq!(ring.get_hashes(&nodes[1]).len(), 3);
+}
+
+#[test]
+fn test_clear() {
+    let nodes = [0, 1, 2];
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 3);
+    }
+    ring.clear();
+    assert!(ring.is_empty());
+    assert_eq!(ring.len(), 0);
+
+    ring.insert_node(&nodes[0], 3);
+    assert_eq!(ring.get_node(&0), &nodes[0]);
+}
+
+#[test]
+fn test_get_nodes() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 2);
+    }
+
+    for point in 0..100 {
+        let ranking = ring.get_nodes(&point, 20);
+        assert_eq!(ranking.len(), nodes.len());
+        assert_eq!(ring.get_nodes(&point, 1)[0], ring.get_node(&point));
+        assert_eq!(ring.get_nodes(&point, 3), &ranking[..3]);
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_skeleton_ring_matches_flat_ring() {
+    let nodes: Vec<u64> = (0..8).collect();
+    let hash_builder = RandomState::new();
+    let mut ring = Ring::with_hasher(hash_builder.clone());
+    let mut skeleton = SkeletonRing::with_hasher(hash_builder, nodes.len());
+    for node in &nodes {
+        ring.insert_node(node, *node as usize % 3 + 1);
+        skeleton.insert_node(node, *node as usize % 3 + 1);
+    }
+    ring.remove_node(&nodes[2]);
+    skeleton.remove_node(&nodes[2]);
+
+    assert_eq!(skeleton.len(), 7);
+    for point in 0..1000 {
+        assert_eq!(skeleton.get_node(&point), ring.get_node(&point));
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_skeleton_ring_lookup_cost() {
+    let nodes: Vec<u64> = (0..5000).collect();
+    let mut skeleton = SkeletonRing::new(8);
+    for node in &nodes {
+        skeleton.insert_node(node, 1);
+    }
+
+    let mut counts = vec![0u64; nodes.len()];
+    for point in 0..50_000u64 {
+        let (node, scores) =
+            skeleton.find(util::gen_hash(&skeleton.hash_builder, &point)).unwrap();
+        assert!(scores <= 8 * 5);
+        counts[*node as usize] += 1;
+    }
+    assert!(counts.iter().all(|count| *count < 40));
+
+    let before: Vec<&u64> = (0..10_000u64).map(|point| skeleton.get_node(&point)).collect();
+    let extra = 5000;
+    skeleton.insert_node(&extra, 1);
+    let moved = (0..10_000u64)
+        .zip(before)
+        .filter(|(point, node)| skeleton.get_node(point) != *node)
+        .count();
+    assert!(moved < 40);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_skeleton_ring_replica_weights() {
+    let nodes: Vec<u64> = (0..8).collect();
+    let mut skeleton = SkeletonRing::new(2);
+    skeleton.insert_node(&nodes[0], 10);
+    for node in &nodes[1..] {
+        skeleton.insert_node(node, 1);
+    }
+
+    let points = 20_000;
+    let heavy = (0..points).filter(|point| *skeleton.get_node(point) == nodes[0]).count();
+    assert!((heavy as f64 / points as f64 - 10.0 / 17.0).abs() < 0.03);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_skeleton_ring_remove_node() {
+    let nodes: Vec<u64> = (0..64).collect();
+    let mut skeleton = SkeletonRing::new(4);
+    for node in &nodes {
+        skeleton.insert_node(node, 1);
+    }
+    let points: Vec<u64> = (0..10_000).collect();
+    let before: Vec<&u64> = points.iter().map(|point| skeleton.get_node(point)).collect();
+
+    skeleton.remove_node(&nodes[10]);
+    assert_eq!(skeleton.len(), 63);
+    let mut moved = 0;
+    for (point, node) in points.iter().zip(&before) {
+        let after = skeleton.get_node(point);
+        assert_ne!(after, &nodes[10]);
+        if **node != nodes[10] && after != *node {
+            moved += 1;
+        }
+    }
+    assert!(moved < 2 * points.len() / nodes.len());
+
+    skeleton.insert_node(&nodes[10], 1);
+    assert!(points.iter().zip(&before).all(|(point, node)| skeleton.get_node(point) == *node));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_skeleton_ring_try_get_node() {
+    let nodes = [0, 1];
+    let mut skeleton = SkeletonRing::new(2);
+    assert_eq!(skeleton.try_get_node(&0), Err(HashRingError::EmptyRing));
+    skeleton.insert_node(&nodes[0], 0);
+    assert_eq!(skeleton.try_get_node(&0), Err(HashRingError::EmptyRing));
+
+    skeleton.insert_node(&nodes[1], 1);
+    assert_eq!(skeleton.try_get_node(&0), Ok(&nodes[1]));
+    skeleton.remove_node(&nodes[1]);
+    skeleton.remove_node(&nodes[0]);
+    assert!(skeleton.is_empty());
+    assert_eq!(skeleton.try_get_node(&0), Err(HashRingError::EmptyRing));
+}
+
+#[test]
+fn test_try_get_node() {
+    let node = 0;
+    let mut ring = Ring::new();
+    assert_eq!(ring.try_get_node(&0), Err(HashRingError::EmptyRing));
+
+    ring.insert_node(&node, 1);
+    assert_eq!(ring.try_get_node(&0), Ok(&node));
+}
+
+#[test]
+fn test_get_node_batch() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let points: Vec<u64> = (0..1000).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 3);
+    }
+
+    let expected: Vec<&u64> = points.iter().map(|point| ring.get_node(point)).collect();
+    assert_eq!(ring.get_node_batch(&points), expected);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_owned_ring() {
+    let hash_builder = RandomState::new();
+    let mut ring = OwnedRing::with_hasher(hash_builder.clone());
+    let expected: Vec<String> = {
+        let ids: Vec<String> = (0..10).map(|node| format!("node-{}", node)).collect();
+        let mut borrowed = Ring::with_hasher(hash_builder);
+        for id in &ids {
+            borrowed.insert_node(id, 2);
+            ring.insert_node(id.clone(), 2);
+        }
+        (0..100u64).map(|point| borrowed.get_node(&point).clone()).collect()
+    };
+
+    assert_eq!(ring.len(), 10);
+    for (point, id) in (0..100u64).zip(&expected) {
+        assert_eq!(ring.get_node(&point), id);
+    }
+    assert_eq!(ring.remove_node(&expected[0]), Some(expected[0].clone()));
+    assert_eq!(ring.remove_node(&expected[0]), None);
+    assert_eq!(ring.node_ids().count(), 9);
+}
+
+#[test]
+fn test_contains_node() {
+    let nodes = [0, 1];
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 1);
+
+    assert!(ring.contains_node(&nodes[0]));
+    assert!(!ring.contains_node(&nodes[1]));
+    ring.remove_node(&nodes[0]);
+    assert!(!ring.contains_node(&nodes[0]));
+}
+
+#[test]
+fn test_keys_for_node() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let keys: Vec<u64> = (0..1000).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 1);
+    }
+
+    let mut assigned: Vec<u64> = Vec::new();
+    for node in &nodes {
+        let node_keys = ring.keys_for_node(node, &keys);
+        assert!(node_keys.iter().all(|key| ring.get_node(*key) == node));
+        assigned.extend(node_keys);
+    }
+    assigned.sort_unstable();
+    assert_eq!(assigned, keys);
+    assert!(ring.keys_for_node(&10, &keys).is_empty());
+}
@@ -1,4 +1,150 @@
-use std::hash::{BuildHasher, Hash, Hasher};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use core::hash::{BuildHasher, Hash, Hasher};
+use siphasher::sip::SipHasher;
+
+/// A point that has already been hashed.
+///
+/// Hashing a point once and looking it up in several rings that share a hash builder avoids
+/// hashing the point for every ring. Multi-probe consistent hashing and maglev hashing use their
+/// own hashers and do not accept hashed points.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HashedPoint(u64);
+
+impl HashedPoint {
+    /// Returns the hash of the point.
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+/// Hashes a point so it can be looked up in rings that use the same hash builder.
+pub fn hash_point<T, H>(hash_builder: &H, point: &T) -> HashedPoint
+    where
+        T: Hash,
+        H: BuildHasher,
+{
+    HashedPoint(gen_hash(hash_builder, point))
+}
+
+/// Hashes a point combined with a salt so it can be looked up in rings that use the same hash
+/// builder.
+///
+/// The same point with different salts is mapped to nodes independently, so salting points with a
+/// tenant id prevents the points of one tenant from revealing where the points of another tenant
+/// are mapped.
+pub fn hash_point_salted<T, S, H>(hash_builder: &H, point: &T, salt: &S) -> HashedPoint
+    where
+        T: Hash,
+        S: Hash,
+        H: BuildHasher,
+{
+    HashedPoint(combine_hash(
+        hash_builder,
+        gen_hash(hash_builder, point),
+        gen_hash(hash_builder, salt),
+    ))
+}
+
+/// Mixes a `u64` into a pseudorandom `u64` using the finalizer of SplitMix64.
+///
+/// Mixing does not allocate or construct a hasher, so mixing a slice of integers compiles to a
+/// tight loop that can be vectorized. `IntegerState` hashes a `u64` to the same value.
+pub fn mix_u64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Returns the two SipHash hashers that `maglev` and `mpc` derive their hashes from. The first is
+/// keyed by `keys` and the second by `keys` mixed with `mix_u64`.
+#[cfg(feature = "std")]
+pub(crate) fn get_keyed_hashers(keys: (u64, u64)) -> [SipHasher; 2] {
+    [
+        SipHasher::new_with_keys(keys.0, keys.1),
+        SipHasher::new_with_keys(mix_u64(keys.0), mix_u64(keys.1)),
+    ]
+}
+
+/// A hash builder that hashes integers with `mix_u64`.
+///
+/// Rings that use `IntegerState` can look up `u64` keys in bulk without constructing a hasher for
+/// every key. The hasher is not keyed, so it should not be used for keys that are controlled by
+/// an adversary.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IntegerState;
+
+/// The hasher built by `IntegerState`.
+#[derive(Debug, Default)]
+pub struct IntegerHasher {
+    state: u64,
+}
+
+impl Hasher for IntegerHasher {
+    fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut buffer = [0; 8];
+            buffer[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(buffer));
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.state = mix_u64(self.state ^ value);
+    }
+}
+
+impl BuildHasher for IntegerState {
+    type Hasher = IntegerHasher;
+
+    fn build_hasher(&self) -> IntegerHasher {
+        IntegerHasher::default()
+    }
+}
+
+/// A hash builder that builds SipHash hashers with fixed keys.
+///
+/// Unlike `RandomState`, rings that use `SipState` with the same keys hash every value to the same
+/// position in every process, so they can be persisted and restored. With the `serde` feature,
+/// the keys can be serialized along with a ring.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SipState {
+    k0: u64,
+    k1: u64,
+}
+
+impl SipState {
+    /// Constructs a new `SipState` with the specified keys.
+    pub fn new(k0: u64, k1: u64) -> Self {
+        Self { k0, k1 }
+    }
+}
+
+impl BuildHasher for SipState {
+    type Hasher = SipHasher;
+
+    fn build_hasher(&self) -> SipHasher {
+        SipHasher::new_with_keys(self.k0, self.k1)
+    }
+}
+
+/// The default hash builder of the rings that are available without the standard library.
+///
+/// It is `RandomState` with the `std` feature and `SipState` with keys of zero without it.
+#[cfg(feature = "std")]
+pub type DefaultState = std::collections::hash_map::RandomState;
+
+/// The default hash builder of the rings that are available without the standard library.
+///
+/// It is `RandomState` with the `std` feature and `SipState` with keys of zero without it.
+#[cfg(not(feature = "std"))]
+pub type DefaultState = SipState;
 
 pub fn gen_hash<T, H>(hash_builder: &H, value: &T) -> u64
     where
@@ -10,6 +156,29 @@ pub fn gen_hash<T, H>(hash_builder: &H, value: &T) -> u64
     hasher.finish()
 }
 
+/// Hashes a sequence of bytes without collecting it first. The bytes are written to the hasher in
+/// chunks, so the hash is the same as writing all the bytes at once for hashers that do not depend
+/// on how their input is split, such as SipHash.
+pub fn gen_hash_streaming<I, H>(hash_builder: &H, bytes: I) -> u64
+    where
+        I: IntoIterator<Item = u8>,
+        H: BuildHasher,
+{
+    let mut hasher = hash_builder.build_hasher();
+    let mut buffer = [0; 64];
+    let mut len = 0;
+    for byte in bytes {
+        buffer[len] = byte;
+        len += 1;
+        if len == buffer.len() {
+            hasher.write(&buffer);
+            len = 0;
+        }
+    }
+    hasher.write(&buffer[..len]);
+    hasher.finish()
+}
+
 pub fn combine_hash<H>(hash_builder: &H, x: u64, y: u64) -> u64
     where
         H: BuildHasher,
@@ -18,4 +187,151 @@ pub fn combine_hash<H>(hash_builder: &H, x: u64, y: u64) -> u64
     x.hash(&mut hasher);
     y.hash(&mut hasher);
     hasher.finish()
-}
\ No newline at end of file
+}
+
+/// Returns a fingerprint of a set of entries that does not depend on the order of the entries.
+///
+/// The entries are hashed with fixed keys rather than the hash builder of a ring, so rings that are
+/// in different processes have the same fingerprint if they have the same entries.
+pub fn gen_fingerprint<I>(entries: I) -> u64
+    where
+        I: IntoIterator,
+        I::Item: Hash,
+{
+    let mut count = 0u64;
+    let mut sum = 0u64;
+    for entry in entries {
+        let mut hasher = SipHasher::new_with_keys(0, 0);
+        entry.hash(&mut hasher);
+        sum = sum.wrapping_add(hasher.finish());
+        count += 1;
+    }
+    let mut hasher = SipHasher::new_with_keys(0, 0);
+    (count, sum).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns the Gini coefficient of the loads of a set of nodes.
+///
+/// The coefficient is 0 if every node has the same load and approaches 1 as the load is
+/// concentrated on a single node. Returns 0 if there are no nodes or no load.
+pub fn gini_coefficient(loads: &[f64]) -> f64 {
+    let total: f64 = loads.iter().sum();
+    if loads.is_empty() || total == 0.0 {
+        return 0.0;
+    }
+    let mut sorted = loads.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).expect("Expected all non-NaN floats."));
+    let n = sorted.len() as f64;
+    let weighted_sum: f64 = sorted
+        .iter()
+        .enumerate()
+        .map(|(index, load)| (2.0 * (index + 1) as f64 - n - 1.0) * load)
+        .sum();
+    weighted_sum / (n * total)
+}
+
+/// Returns Jain's fairness index of the loads of a set of nodes.
+///
+/// The index is 1 if every node has the same load and `1 / n` if the load is concentrated on a
+/// single node out of `n`. Returns 1 if there are no nodes or no load.
+pub fn fairness_index(loads: &[f64]) -> f64 {
+    let total: f64 = loads.iter().sum();
+    let total_squares: f64 = loads.iter().map(|load| load * load).sum();
+    if loads.is_empty() || total_squares == 0.0 {
+        return 1.0;
+    }
+    total * total / (loads.len() as f64 * total_squares)
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_gen_hash_streaming() {
+    let hash_builder = std::collections::hash_map::RandomState::new();
+    let bytes: Vec<u8> = (0..1000).map(|i| (i % 256) as u8).collect();
+    let mut hasher = hash_builder.build_hasher();
+    hasher.write(&bytes);
+
+    assert_eq!(
+        gen_hash_streaming(&hash_builder, bytes.iter().cloned()),
+        hasher.finish(),
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_hash_point() {
+    use crate::{carp, consistent, jump, rendezvous, weighted_rendezvous};
+    use std::collections::hash_map::RandomState;
+
+    let hash_builder = RandomState::new();
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut consistent = consistent::Ring::with_hasher(hash_builder.clone());
+    let mut rendezvous = rendezvous::Ring::with_hasher(hash_builder.clone());
+    let mut weighted_rendezvous = weighted_rendezvous::Ring::with_hasher(hash_builder.clone());
+    let carp = carp::Ring::with_hasher(
+        hash_builder.clone(),
+        nodes.iter().map(|node| carp::Node::new(node, (node + 1) as f64)).collect(),
+    );
+    let jump = jump::Ring::with_hasher(hash_builder.clone(), 10);
+    for node in &nodes {
+        consistent.insert_node(node, 10);
+        rendezvous.insert_node(node, 2);
+        weighted_rendezvous.insert_node(node, (node + 1) as f64);
+    }
+
+    for point in 0..1000 {
+        let hashed_point = hash_point(&hash_builder, &point);
+        assert_eq!(consistent.get_node_prehashed(&hashed_point), consistent.get_node(&point));
+        assert_eq!(rendezvous.get_node_prehashed(&hashed_point), rendezvous.get_node(&point));
+        assert_eq!(
+            weighted_rendezvous.get_node_prehashed(&hashed_point),
+            weighted_rendezvous.get_node(&point),
+        );
+        assert_eq!(carp.get_node_prehashed(&hashed_point), carp.get_node(&point));
+        assert_eq!(jump.get_node_prehashed(&hashed_point), jump.get_node(&point));
+    }
+}
+
+#[test]
+fn test_gini_coefficient() {
+    assert_eq!(gini_coefficient(&[]), 0.0);
+    assert_eq!(gini_coefficient(&[5.0, 5.0, 5.0, 5.0]), 0.0);
+    assert!((gini_coefficient(&[0.0, 0.0, 0.0, 4.0]) - 0.75).abs() < 1e-9);
+    assert!((gini_coefficient(&[3.0, 1.0, 2.0]) - 2.0 / 9.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_fairness_index() {
+    assert_eq!(fairness_index(&[]), 1.0);
+    assert!((fairness_index(&[5.0, 5.0, 5.0, 5.0]) - 1.0).abs() < 1e-9);
+    assert!((fairness_index(&[0.0, 0.0, 0.0, 4.0]) - 0.25).abs() < 1e-9);
+    assert!((fairness_index(&[1.0, 2.0, 3.0]) - 36.0 / 42.0).abs() < 1e-9);
+}
+
+/// A hash builder whose hashers ignore their input and always return zero. It is used in tests to
+/// force hash collisions.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct ConstantState;
+
+#[cfg(test)]
+pub struct ConstantHasher;
+
+#[cfg(test)]
+impl Hasher for ConstantHasher {
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    fn write(&mut self, _bytes: &[u8]) {}
+}
+
+#[cfg(test)]
+impl BuildHasher for ConstantState {
+    type Hasher = ConstantHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        ConstantHasher
+    }
+}
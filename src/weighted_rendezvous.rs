@@ -1,16 +1,44 @@
-use crate::util;
+use crate::util::{self, HashedPoint};
+use crate::{DynamicHashRing, HashRing, HashRingError, Router};
+use std::cmp::Ordering;
 use std::collections::hash_map::RandomState;
 use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
 use std::hash::{BuildHasher, Hash};
+use std::mem;
 use std::vec::Vec;
 
+/// An error returned by `Ring::set_weight`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeightError {
+    /// The weight is zero, negative or NaN.
+    NotPositive,
+    /// The weight is infinite.
+    NotFinite,
+}
+
+impl fmt::Display for WeightError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WeightError::NotPositive => write!(f, "weight is not positive"),
+            WeightError::NotFinite => write!(f, "weight is not finite"),
+        }
+    }
+}
+
+impl Error for WeightError {}
+
 /// A hashing ring implemented using weighted rendezvous hashing.
 ///
 /// Rendezvous hashing is based on based on assigning a pseudorandom value to node-point pair.
 /// A point is mapped to the node that yields the greatest value associated with the node-point
 /// pair.
+///
+/// Each node also has a health score in `[0, 1]` that its weight is multiplied by, so the share of
+/// an unhealthy node can be reduced without removing it from the ring.
 pub struct Ring<'a, T, H = RandomState> {
-    nodes: HashMap<&'a T, f64>,
+    nodes: HashMap<&'a T, (f64, f64)>,
     hash_builder: H,
 }
 
@@ -41,12 +69,80 @@ impl<'a, T, H> Ring<'a, T, H> {
     ///
     /// Increasing the weight will increase the number of expected points mapped to the node. For
     /// example, a node with a weight of three will receive approximately three times more points
-    /// than a node with a weight of one.
+    /// than a node with a weight of one. A node whose weight is not positive, or is NaN, receives
+    /// no points unless every node in the ring has such a weight.
+    ///
+    /// A new node has a health score of one. If the node is already in the ring, its health score
+    /// is kept.
     pub fn insert_node(&mut self, id: &'a T, weight: f64)
         where
             T: Hash + Eq,
     {
-        self.nodes.insert(id, weight);
+        let health = self.nodes.get(id).map_or(1.0, |entry| entry.1);
+        self.nodes.insert(id, (weight, health));
+    }
+
+    /// Sets the weight of a node, inserting the node if it is not in the ring. The health score of
+    /// the node is kept.
+    ///
+    /// Unlike `insert_node`, the weight is validated. If it is not positive and finite, an error
+    /// is returned and the ring is not changed. Use `set_health` to reduce the share of a node to
+    /// zero.
+    pub fn set_weight(&mut self, id: &'a T, weight: f64) -> Result<(), WeightError>
+        where
+            T: Hash + Eq,
+    {
+        if weight.is_infinite() {
+            return Err(WeightError::NotFinite);
+        }
+        if weight.is_nan() || weight <= 0.0 {
+            return Err(WeightError::NotPositive);
+        }
+        self.insert_node(id, weight);
+        Ok(())
+    }
+
+    /// Returns the weight of a node, or `None` if the node is not in the ring. The weight does not
+    /// include the health score of the node.
+    pub fn weight(&self, id: &T) -> Option<f64>
+        where
+            T: Hash + Eq,
+    {
+        self.nodes.get(id).map(|entry| entry.0)
+    }
+
+    /// Sets the health score of a node, which must be in `[0, 1]`. The effective weight of the node
+    /// is its weight multiplied by its health score, and a node with a health score of zero is
+    /// only returned if every node has a health score of zero.
+    pub fn set_health(&mut self, id: &T, score: f64)
+        where
+            T: Hash + Eq,
+    {
+        assert!((0.0..=1.0).contains(&score));
+        if let Some(entry) = self.nodes.get_mut(id) {
+            entry.1 = score;
+        }
+    }
+
+    /// Returns the health score of a node, or `None` if the node is not in the ring.
+    pub fn health(&self, id: &T) -> Option<f64>
+        where
+            T: Hash + Eq,
+    {
+        self.nodes.get(id).map(|entry| entry.1)
+    }
+
+    /// Removes every node from the ring. The hash builder of the ring is kept.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
+    /// Returns `true` if the node is in the ring.
+    pub fn contains_node(&self, id: &T) -> bool
+        where
+            T: Hash + Eq,
+    {
+        self.nodes.contains_key(id)
     }
 
     /// Removes a node from the ring.
@@ -57,36 +153,123 @@ impl<'a, T, H> Ring<'a, T, H> {
         self.nodes.remove(id);
     }
 
+    // The score of a node is `-weight / ln(ratio)`, where `ratio` is the hash mapped to `[0, 1]`.
+    // The logarithm of the score, `ln(weight) - ln(-ln(ratio))`, has the same ordering, but it
+    // does not overflow for large weights and ratios close to one.
+    fn get_score(weight: f64, hash: u64) -> f64 {
+        if weight.is_nan() || weight <= 0.0 {
+            return f64::NEG_INFINITY;
+        }
+        let ratio = hash as f64 / u64::MAX as f64;
+        weight.ln() - (-ratio.ln()).ln()
+    }
+
     /// Returns the node associated with a point.
+    ///
+    /// If several nodes share the greatest score for the point, the node with the lowest id is
+    /// returned.
     pub fn get_node<U>(&self, point: &U) -> &'a T
         where
             T: Hash + Ord,
             U: Hash,
             H: BuildHasher,
     {
-        let point_hash = util::gen_hash(&self.hash_builder, point);
-        self.nodes
-            .iter()
-            .map(|entry| {
-                let hash = util::combine_hash(
-                    &self.hash_builder,
-                    util::gen_hash(&self.hash_builder, entry.0),
-                    point_hash,
-                );
-                (
-                    -entry.1 / (hash as f64 / u64::max_value() as f64).ln(),
-                    entry.0,
-                )
-            })
-            .max_by(|n, m| {
-                if n == m {
-                    n.1.cmp(m.1)
-                } else {
-                    n.0.partial_cmp(&m.0).expect("Expected all non-NaN floats.")
-                }
-            })
-            .expect("Expected non-empty ring.")
-            .1
+        self.try_get_node(point).expect("Expected non-empty ring.")
+    }
+
+    /// Returns the node associated with a point, or `HashRingError::EmptyRing` if the ring is
+    /// empty.
+    pub fn try_get_node<U>(&self, point: &U) -> Result<&'a T, HashRingError>
+        where
+            T: Hash + Ord,
+            U: Hash,
+            H: BuildHasher,
+    {
+        self.find(util::gen_hash(&self.hash_builder, point)).ok_or(HashRingError::EmptyRing)
+    }
+
+    /// Returns the node associated with a point that was hashed by `util::hash_point` with the
+    /// same hash builder as the ring.
+    pub fn get_node_prehashed(&self, point: &HashedPoint) -> &'a T
+        where
+            T: Hash + Ord,
+            H: BuildHasher,
+    {
+        self.find(point.value()).expect("Expected non-empty ring.")
+    }
+
+    fn find(&self, point_hash: u64) -> Option<&'a T>
+        where
+            T: Hash + Ord,
+            H: BuildHasher,
+    {
+        self.get_scores(point_hash)
+            .min_by(Self::cmp_scores)
+            .map(|entry| entry.1)
+    }
+
+    fn get_scores(&self, point_hash: u64) -> impl Iterator<Item = (f64, &'a T)> + '_
+        where
+            T: Hash,
+            H: BuildHasher,
+    {
+        self.nodes.iter().map(move |entry| {
+            let hash = util::combine_hash(
+                &self.hash_builder,
+                util::gen_hash(&self.hash_builder, entry.0),
+                point_hash,
+            );
+            let (weight, health) = entry.1;
+            (Self::get_score(weight * health, hash), *entry.0)
+        })
+    }
+
+    // Orders scores from the greatest score to the lowest score, breaking ties by the lowest id.
+    fn cmp_scores(n: &(f64, &'a T), m: &(f64, &'a T)) -> Ordering
+        where
+            T: Ord,
+    {
+        m.0.partial_cmp(&n.0)
+            .expect("Expected all non-NaN floats.")
+            .then_with(|| n.1.cmp(m.1))
+    }
+
+    /// Returns up to `n` nodes associated with a point, ordered from the greatest score to the
+    /// lowest score. The first node is the node returned by `get_node`.
+    ///
+    /// All the nodes of the ring are returned if the ring has fewer than `n` nodes.
+    pub fn get_nodes<U>(&self, point: &U, n: usize) -> Vec<&'a T>
+        where
+            T: Hash + Ord,
+            U: Hash,
+            H: BuildHasher,
+    {
+        let mut scores: Vec<(f64, &'a T)> = self
+            .get_scores(util::gen_hash(&self.hash_builder, point))
+            .collect();
+        scores.sort_by(Self::cmp_scores);
+        scores.into_iter().take(n).map(|entry| entry.1).collect()
+    }
+
+    /// Returns a fingerprint of the nodes of the ring and their weights and health scores.
+    ///
+    /// Rings with the same nodes, weights and health scores have the same fingerprint, regardless
+    /// of the order the nodes were inserted in or the hash builder of the ring.
+    pub fn fingerprint(&self) -> u64
+        where
+            T: Hash,
+    {
+        util::gen_fingerprint(
+            self.nodes
+                .iter()
+                .map(|(id, entry)| (id, entry.0.to_bits(), entry.1.to_bits())),
+        )
+    }
+
+    /// Returns an estimate of the number of bytes used by the ring: every node with its weight and
+    /// health score.
+    pub fn memory_footprint(&self) -> usize {
+        mem::size_of::<Self>() + self.nodes.len() * mem::size_of::<(&T, (f64, f64))>()
     }
 
     /// Returns the number of nodes in the ring.
@@ -114,4 +297,243 @@ impl<'a, T, H> Default for Ring<'a, T, H>
     fn default() -> Self {
         Self::with_hasher(Default::default())
     }
-}
\ No newline at end of file
+}
+
+impl<'a, T, U, H> HashRing<'a, T, U> for Ring<'a, T, H>
+    where
+        T: Hash + Ord,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn get_node(&self, point: &U) -> &'a T {
+        self.get_node(point)
+    }
+
+    fn try_get_node(&self, point: &U) -> Result<&'a T, HashRingError> {
+        self.try_get_node(point)
+    }
+
+    fn node_ids(&self) -> Vec<&'a T> {
+        self.nodes.keys().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<'a, T, U, H> DynamicHashRing<'a, T, U> for Ring<'a, T, H>
+    where
+        T: Hash + Ord,
+        U: Hash,
+        H: BuildHasher,
+{
+    fn remove_node(&mut self, id: &T) {
+        self.remove_node(id)
+    }
+}
+
+impl<'a, T, H> Router for Ring<'a, T, H>
+    where
+        T: Hash + Ord,
+        H: BuildHasher,
+{
+    type Output = &'a T;
+
+    fn route<K>(&self, key: &K) -> &'a T
+        where
+            K: Hash,
+    {
+        self.get_node(key)
+    }
+}
+
+#[test]
+fn test_get_score() {
+    type R<'a> = Ring<'a, u64>;
+    let legacy_score = |weight: f64, hash: u64| -weight / (hash as f64 / u64::MAX as f64).ln();
+    let mut scores = Vec::new();
+    for weight in &[0.5, 1.0, 3.0, 1e9] {
+        for hash in &[1, 1 << 20, 1 << 40, 1 << 62, u64::MAX / 3, u64::MAX - (1 << 40)] {
+            scores.push((R::get_score(*weight, *hash), legacy_score(*weight, *hash)));
+        }
+    }
+
+    for (score, legacy) in &scores {
+        for (other_score, other_legacy) in &scores {
+            assert_eq!(score.partial_cmp(other_score), legacy.partial_cmp(other_legacy));
+        }
+    }
+    assert_eq!(R::get_score(1e9, u64::MAX), f64::INFINITY);
+    assert_eq!(R::get_score(1e9, 0), f64::NEG_INFINITY);
+    assert!(R::get_score(1e300, 1).is_finite());
+    assert!(R::get_score(1e300, u64::MAX - (1 << 40)).is_finite());
+}
+
+#[test]
+fn test_extreme_weights() {
+    let nodes = [0, 1];
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 1.0);
+    ring.insert_node(&nodes[1], 1e9);
+
+    let heavy = (0..10_000u64)
+        .filter(|point| *ring.get_node(point) == nodes[1])
+        .count();
+    assert!(heavy > 9_990);
+}
+
+#[test]
+fn test_non_positive_weights() {
+    let nodes = [0, 1, 2];
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 1.0);
+    ring.insert_node(&nodes[1], -1.0);
+    ring.insert_node(&nodes[2], f64::NAN);
+
+    for point in 0..1000 {
+        assert_eq!(ring.get_node(&point), &nodes[0]);
+    }
+    assert_eq!(Ring::<u64>::get_score(-1.0, 1 << 40), f64::NEG_INFINITY);
+}
+
+#[test]
+fn test_fingerprint() {
+    let nodes = [0, 1, 2];
+    let mut ring = Ring::new();
+    let mut other = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 1.0);
+        other.insert_node(node, 1.0);
+    }
+    assert_eq!(ring.fingerprint(), other.fingerprint());
+
+    other.insert_node(&nodes[2], 2.0);
+    assert_ne!(ring.fingerprint(), other.fingerprint());
+}
+
+#[test]
+fn test_fingerprint_health() {
+    let nodes = [0, 1, 2];
+    let mut ring = Ring::new();
+    let mut other = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 1.0);
+        other.insert_node(node, 1.0);
+    }
+    other.set_health(&nodes[1], 0.5);
+    assert_ne!(ring.fingerprint(), other.fingerprint());
+
+    other.set_health(&nodes[1], 1.0);
+    assert_eq!(ring.fingerprint(), other.fingerprint());
+}
+
+#[test]
+fn test_set_health() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 1.0);
+    }
+    ring.set_health(&nodes[0], 0.1);
+    ring.insert_node(&nodes[0], 1.0);
+    assert_eq!(ring.health(&nodes[0]), Some(0.1));
+
+    let mut counts = [0u64; 10];
+    for point in 0..100_000u64 {
+        counts[*ring.get_node(&point) as usize] += 1;
+    }
+    let healthy_average = counts[1..].iter().sum::<u64>() as f64 / 9.0;
+    let ratio = healthy_average / counts[0] as f64;
+    assert!(ratio > 8.0 && ratio < 12.0);
+}
+
+#[test]
+fn test_clear() {
+    let nodes = [0, 1, 2];
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, 1.0);
+    }
+    ring.clear();
+    assert!(ring.is_empty());
+    assert_eq!(ring.len(), 0);
+
+    ring.insert_node(&nodes[0], 1.0);
+    assert_eq!(ring.get_node(&0), &nodes[0]);
+}
+
+#[test]
+fn test_try_get_node() {
+    let node = 0;
+    let mut ring = Ring::new();
+    assert_eq!(ring.try_get_node(&0), Err(HashRingError::EmptyRing));
+
+    ring.insert_node(&node, 1.0);
+    assert_eq!(ring.try_get_node(&0), Ok(&node));
+}
+
+#[test]
+fn test_set_weight() {
+    let nodes = [0, 1];
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 1.0);
+    ring.set_health(&nodes[0], 0.5);
+    assert_eq!(ring.set_weight(&nodes[0], 2.0), Ok(()));
+    assert_eq!(ring.set_weight(&nodes[1], 3.0), Ok(()));
+
+    assert_eq!(ring.weight(&nodes[0]), Some(2.0));
+    assert_eq!(ring.health(&nodes[0]), Some(0.5));
+    assert_eq!(ring.weight(&nodes[1]), Some(3.0));
+    assert_eq!(ring.weight(&2), None);
+}
+
+#[test]
+fn test_set_invalid_weight() {
+    let node = 0;
+    let mut ring = Ring::new();
+    ring.insert_node(&node, 1.0);
+
+    assert_eq!(ring.set_weight(&node, 0.0), Err(WeightError::NotPositive));
+    assert_eq!(ring.set_weight(&node, f64::NAN), Err(WeightError::NotPositive));
+    assert_eq!(ring.set_weight(&node, f64::INFINITY), Err(WeightError::NotFinite));
+    assert_eq!(ring.weight(&node), Some(1.0));
+}
+
+#[test]
+fn test_get_nodes() {
+    let nodes: Vec<u64> = (0..10).collect();
+    let mut ring = Ring::new();
+    for node in &nodes {
+        ring.insert_node(node, (node + 1) as f64);
+    }
+
+    let mut ranks = [0usize; 10];
+    for point in 0..1000 {
+        let ranking = ring.get_nodes(&point, 20);
+        assert_eq!(ranking.len(), nodes.len());
+        assert_eq!(ranking[0], ring.get_node(&point));
+        assert_eq!(ring.get_nodes(&point, 3), &ranking[..3]);
+        for (rank, node) in ranking.iter().enumerate() {
+            ranks[**node as usize] += rank;
+        }
+    }
+    assert!(ranks[9] < ranks[4]);
+    assert!(ranks[4] < ranks[0]);
+}
+
+#[test]
+fn test_contains_node() {
+    let nodes = [0, 1];
+    let mut ring = Ring::new();
+    ring.insert_node(&nodes[0], 1.0);
+
+    assert!(ring.contains_node(&nodes[0]));
+    assert!(!ring.contains_node(&nodes[1]));
+    ring.remove_node(&nodes[0]);
+    assert!(!ring.contains_node(&nodes[0]));
+}